@@ -5,8 +5,12 @@ pub mod cli;
 pub mod types;
 pub mod utility;
 
+pub mod check;
+pub mod destructure;
 pub mod environment;
 pub mod eval;
+pub mod module;
 pub mod parse;
+pub mod pattern;
 pub mod read;
 pub mod value;
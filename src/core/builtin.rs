@@ -3,4 +3,5 @@
 pub mod constants;
 pub mod functions;
 pub mod generators;
+pub mod higher_order;
 pub mod macros;
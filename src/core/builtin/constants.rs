@@ -13,6 +13,7 @@ pub static SYMBOL_PI: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Mathematical constant pi."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("*pi*"),
 });
@@ -23,6 +24,7 @@ pub static SYMBOL_E: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Mathematical constant logarithmic base e."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("*e*"),
 });
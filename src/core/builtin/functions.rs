@@ -3,6 +3,7 @@
 use std::any::Any;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::num::NonZeroUsize;
 use std::rc::Rc;
 use std::{fmt, ptr};
 
@@ -11,13 +12,18 @@ use rand::seq::SliceRandom;
 use rand::Rng;
 use unescape;
 
-use crate::core::builtin::generators::Range;
+use crate::core::builtin::generators::{yield_value, LazyGenerator, Range};
+use crate::core::builtin::higher_order::{Comp, Complement, Constantly, Fnil, Juxt, Memoized, Partial};
+use crate::core::types::atom::Atom;
+use crate::core::types::error::exception_value;
 use crate::core::types::error::Error;
 use crate::core::types::error::{arity_error, arity_error_min, cannot_compare_error, type_error};
 use crate::core::types::error::{arity_error_range, Result};
 use crate::core::types::function::Function;
+use crate::core::types::generator::{is_lazy, Cursor};
 use crate::core::types::list::List;
 use crate::core::types::meta::Meta;
+use crate::core::types::r#type::Type;
 use crate::core::types::sliceable::Sliceable;
 use crate::core::types::symbol::Symbol;
 use crate::core::types::vector::Vector;
@@ -29,6 +35,7 @@ pub static SYMBOL_TYPE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the type of a value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("type"),
 });
@@ -52,12 +59,76 @@ impl fmt::Display for TypeFn {
     }
 }
 
+// type-of
+pub static SYMBOL_TYPE_OF: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("type-of"),
+    meta: Meta {
+        doc: Cow::Borrowed("Get the type of a value as a type: (type-of 1) => #i64."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("type-of"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeOfFn;
+
+impl Function for TypeOfFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Ok(Value::Type(Type::from(args[0].type_of())))
+    }
+}
+
+impl fmt::Display for TypeOfFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: type-of>")
+    }
+}
+
+// isa?
+pub static SYMBOL_ISA: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("isa?"),
+    meta: Meta {
+        doc: Cow::Borrowed("Return true if the value is of the type or one of its subtypes: (isa? 1 #number)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("isa?"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsaFn;
+
+impl Function for IsaFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        match &args[1] {
+            Value::Type(t) => Ok(Value::Bool(t.matches(&args[0]))),
+            _ => Err(type_error("type", args[1].type_name().as_str())),
+        }
+    }
+}
+
+impl fmt::Display for IsaFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: isa?>")
+    }
+}
+
 // print
 pub static SYMBOL_PRINT: Lazy<Symbol> = Lazy::new(|| Symbol {
     name: Cow::Borrowed("print"),
     meta: Meta {
         doc: Cow::Borrowed("Print value(s) to stdout."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("print"),
 });
@@ -94,6 +165,7 @@ pub static SYMBOL_INPUT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Read a line from stdin."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("input"),
 });
@@ -125,6 +197,7 @@ pub static SYMBOL_INC: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Increment a value by 1."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("inc"),
 });
@@ -158,6 +231,7 @@ pub static SYMBOL_DEC: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Decrement a value by 1."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("dec"),
 });
@@ -199,6 +273,7 @@ pub static SYMBOL_ADD: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Adds all values. (+) returns 0."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("+"),
 });
@@ -229,6 +304,7 @@ pub static SYMBOL_SUB: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Subtracts all remaining values from the first value. (- x) returns -x."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("-"),
 });
@@ -268,6 +344,7 @@ pub static SYMBOL_MUL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Multiplies all values. (*) returns 1."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("*"),
 });
@@ -298,6 +375,7 @@ pub static SYMBOL_DIV: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Divide the first value by all remaining values."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("/"),
 });
@@ -333,6 +411,7 @@ pub static SYMBOL_FLOORDIV: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Divide the first value by all remaining values and floor."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("//"),
 });
@@ -368,6 +447,7 @@ pub static SYMBOL_REM: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Remainder of the first value divided by the second value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("%"),
 });
@@ -401,6 +481,7 @@ pub static SYMBOL_EQUAL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if all values are equal to each other and false otherwise. (= x) returns true."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("="),
 });
@@ -439,6 +520,7 @@ pub static SYMBOL_NOTEQUAL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns false if all values are equal to each other and true otherwise. (!= x) returns false."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("!="),
 });
@@ -477,6 +559,7 @@ pub static SYMBOL_IS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Check if two values are the same."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("is"),
 });
@@ -507,6 +590,7 @@ pub static SYMBOL_GE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if all left values are greater than or equal to the right value. (>= x) returns true."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash(">="),
 });
@@ -548,6 +632,7 @@ pub static SYMBOL_GT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if all left values are greater than the right value. (> x) returns true."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash(">"),
 });
@@ -588,6 +673,7 @@ pub static SYMBOL_LE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if all left values are less than or equal to the right value. (<= x) returns true."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("<="),
 });
@@ -628,6 +714,7 @@ pub static SYMBOL_LT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if all left values are less than the right value. (< x) returns true."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("<"),
 });
@@ -669,6 +756,7 @@ pub static SYMBOL_XOR: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if one of the values is truthy and the other is falsy."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("xor"),
 });
@@ -697,6 +785,7 @@ pub static SYMBOL_NOT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is falsy or nil."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("not"),
 });
@@ -725,6 +814,7 @@ pub static SYMBOL_ZEROQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is zero."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("zero?"),
 });
@@ -757,6 +847,7 @@ pub static SYMBOL_NILQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is nil."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("nil?"),
 });
@@ -785,6 +876,7 @@ pub static SYMBOL_TRUEQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is true."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("true?"),
 });
@@ -813,6 +905,7 @@ pub static SYMBOL_FALSEQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is false."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("false?"),
 });
@@ -841,6 +934,7 @@ pub static SYMBOL_NUMBERQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("number?"),
 });
@@ -869,6 +963,7 @@ pub static SYMBOL_I64Q: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is an i64."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("i64?"),
 });
@@ -897,6 +992,7 @@ pub static SYMBOL_F64Q: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is an f64."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("f64?"),
 });
@@ -925,6 +1021,7 @@ pub static SYMBOL_EVENQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is an even number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("even?"),
 });
@@ -956,6 +1053,7 @@ pub static SYMBOL_ODDQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is an odd number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("odd?"),
 });
@@ -987,6 +1085,7 @@ pub static SYMBOL_EMPTYQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is empty."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("empty?"),
 });
@@ -1015,6 +1114,7 @@ pub static SYMBOL_STRINGQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("string?"),
 });
@@ -1043,6 +1143,7 @@ pub static SYMBOL_KEYWORDQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a keyword."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("keyword?"),
 });
@@ -1071,6 +1172,7 @@ pub static SYMBOL_SYMBOLQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a symbol."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("symbol?"),
 });
@@ -1099,6 +1201,7 @@ pub static SYMBOL_LISTQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a list."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("list?"),
 });
@@ -1127,6 +1230,7 @@ pub static SYMBOL_VECTORQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("vector?"),
 });
@@ -1155,6 +1259,7 @@ pub static SYMBOL_MAPQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a map."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("map?"),
 });
@@ -1183,6 +1288,7 @@ pub static SYMBOL_SETQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Returns true if the value is a set."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("set?"),
 });
@@ -1211,6 +1317,7 @@ pub static SYMBOL_STR: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a value to a string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("str"),
 });
@@ -1240,6 +1347,7 @@ pub static SYMBOL_I64: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a value to an i64."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("i64"),
 });
@@ -1269,6 +1377,7 @@ pub static SYMBOL_F64: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a value to an f64."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("f64"),
 });
@@ -1298,6 +1407,7 @@ pub static SYMBOL_LIST: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Cast to a list."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("list"),
 });
@@ -1360,6 +1470,7 @@ pub static SYMBOL_VECTOR: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Cast to a vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("vector"),
 });
@@ -1422,6 +1533,7 @@ pub static SYMBOL_HMAP: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Cast to a map."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("hmap"),
 });
@@ -1461,7 +1573,7 @@ impl Function for HmapFn {
             },
             _ => {
                 let mut map = vec![];
-                if args.len() % 2 != 0 {
+                if !args.len().is_multiple_of(2) {
                     return Err(Error::Value(
                         "hmap: argument must be list, vector, map, set, or even number of arguments".to_string(),
                     ));
@@ -1489,6 +1601,7 @@ pub static SYMBOL_HSET: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Cast to a set."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("hset"),
 });
@@ -1536,6 +1649,7 @@ pub static SYMBOL_FIRST: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the first element of a collection."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("first"),
 });
@@ -1559,13 +1673,8 @@ impl Function for FirstFn {
                     Ok(Value::String(s[0..1].to_string()))
                 }
             }
-            Value::Generator(gen) => {
-                if let Some(value) = gen.borrow().at(0) {
-                    Ok(value)
-                } else {
-                    Ok(Value::Nil)
-                }
-            }
+            // the next value of a lazy generator
+            Value::Generator(gen) => Ok(Cursor::new(Value::Generator(gen))?.next_value()?.unwrap_or(Value::Nil)),
             _ => Err(type_error(
                 "first: argument must be list, vector, string or generator",
                 args[0].type_name().as_str(),
@@ -1586,6 +1695,7 @@ pub static SYMBOL_REST: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the rest of a collection."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("rest"),
 });
@@ -1619,6 +1729,11 @@ impl Function for RestFn {
                     Ok(Value::String(s[1..].to_string()))
                 }
             }
+            // a lazy generator after its next value
+            Value::Generator(gen) if is_lazy(&gen) => {
+                Cursor::new(Value::Generator(gen.clone()))?.next_value()?;
+                Ok(Value::Generator(gen))
+            }
             Value::Generator(gen) => {
                 let mut result = vec![];
                 for i in 1..gen.borrow().len() {
@@ -1636,71 +1751,712 @@ impl Function for RestFn {
     }
 }
 
-impl fmt::Display for RestFn {
+impl fmt::Display for RestFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: rest>")
+    }
+}
+
+// range
+pub static SYMBOL_RANGE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("range"),
+    meta: Meta {
+        doc: Cow::Borrowed("Create a range of i64: (range end), (range start end step), (range) counts up from 0 forever."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("range"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeFn;
+
+impl Function for RangeFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() > 3 {
+            return Err(arity_error_range(0, 3, args.len()));
+        }
+
+        // infinite
+        if args.is_empty() {
+            let mut current = 0;
+            return Ok(lazy("range", move || {
+                current += 1;
+                Ok(Some(Value::I64(current - 1)))
+            }));
+        }
+
+        let mut start = 0;
+        let mut end = 0;
+        let mut step = 1;
+
+        if args.len() == 1 {
+            end = match args[0] {
+                Value::I64(i) => i,
+                _ => return Err(type_error("i64", args[0].type_name().as_str())),
+            };
+        } else if args.len() == 2 {
+            start = match args[0] {
+                Value::I64(i) => i,
+                _ => return Err(type_error("i64", args[0].type_name().as_str())),
+            };
+            end = match args[1] {
+                Value::I64(i) => i,
+                _ => return Err(type_error("i64", args[1].type_name().as_str())),
+            };
+        } else if args.len() == 3 {
+            start = match args[0] {
+                Value::I64(i) => i,
+                _ => return Err(type_error("i64", args[0].type_name().as_str())),
+            };
+            end = match args[1] {
+                Value::I64(i) => i,
+                _ => return Err(type_error("i64", args[1].type_name().as_str())),
+            };
+            step = match args[2] {
+                Value::I64(i) => i,
+                _ => return Err(type_error("i64", args[2].type_name().as_str())),
+            };
+        }
+
+        Ok(Value::Generator(Rc::new(RefCell::new(Range::new(start, end, step)))))
+    }
+}
+
+impl fmt::Display for RangeFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: range>")
+    }
+}
+
+// a generator producing values on demand
+fn lazy(name: &'static str, next: impl FnMut() -> Result<Option<Value>> + 'static) -> Value {
+    Value::Generator(Rc::new(RefCell::new(LazyGenerator::new(name, next))))
+}
+
+fn count_argument(value: &Value) -> Result<usize> {
+    match value {
+        Value::I64(n) if *n >= 0 => Ok(*n as usize),
+        Value::I64(n) => Err(Error::Value(format!("count must not be negative, got {}", n))),
+        _ => Err(type_error("i64", value.type_name().as_str())),
+    }
+}
+
+fn function_argument(value: &Value) -> Result<Rc<dyn Function>> {
+    match value {
+        Value::Function(f) => Ok(f.clone()),
+        _ => Err(type_error("function", value.type_name().as_str())),
+    }
+}
+
+// take
+pub static SYMBOL_TAKE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("take"),
+    meta: Meta {
+        doc: Cow::Borrowed("Lazily take the first n values: (take 3 (range)) => generator of 0 1 2."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("take"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TakeFn;
+
+impl Function for TakeFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let mut n = count_argument(&args[0])?;
+        let mut cursor = Cursor::new(args[1].clone())?;
+        Ok(lazy("take", move || {
+            if n == 0 {
+                return Ok(None);
+            }
+            n -= 1;
+            cursor.next_value()
+        }))
+    }
+}
+
+impl fmt::Display for TakeFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: take>")
+    }
+}
+
+// drop
+pub static SYMBOL_DROP: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("drop"),
+    meta: Meta {
+        doc: Cow::Borrowed("Lazily skip the first n values: (drop 2 [1 2 3]) => generator of 3."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("drop"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropFn;
+
+impl Function for DropFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let mut n = count_argument(&args[0])?;
+        let mut cursor = Cursor::new(args[1].clone())?;
+        Ok(lazy("drop", move || {
+            while n > 0 {
+                n -= 1;
+                if cursor.next_value()?.is_none() {
+                    return Ok(None);
+                }
+            }
+            cursor.next_value()
+        }))
+    }
+}
+
+impl fmt::Display for DropFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: drop>")
+    }
+}
+
+// take-while
+pub static SYMBOL_TAKE_WHILE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("take-while"),
+    meta: Meta {
+        doc: Cow::Borrowed("Lazily take values while the predicate is truthy: (take-while even? [2 4 5 6])."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("take-while"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TakeWhileFn;
+
+impl Function for TakeWhileFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let f = function_argument(&args[0])?;
+        let mut cursor = Cursor::new(args[1].clone())?;
+        let mut done = false;
+        Ok(lazy("take-while", move || {
+            if done {
+                return Ok(None);
+            }
+            match cursor.next_value()? {
+                Some(v) if f.call(vec![v.clone()])?.is_truthy() => Ok(Some(v)),
+                _ => {
+                    done = true;
+                    Ok(None)
+                }
+            }
+        }))
+    }
+}
+
+impl fmt::Display for TakeWhileFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: take-while>")
+    }
+}
+
+// iterate
+pub static SYMBOL_ITERATE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("iterate"),
+    meta: Meta {
+        doc: Cow::Borrowed("Infinite generator of x, (f x), (f (f x)), ...: (iterate inc 0)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("iterate"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IterateFn;
+
+impl Function for IterateFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let f = function_argument(&args[0])?;
+        let x = args[1].clone();
+        let mut previous: Option<Value> = None;
+        Ok(lazy("iterate", move || {
+            // f is called only when the next value is asked for
+            let current = match previous.take() {
+                Some(v) => f.call(vec![v])?,
+                None => x.clone(),
+            };
+            previous = Some(current.clone());
+            Ok(Some(current))
+        }))
+    }
+}
+
+impl fmt::Display for IterateFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: iterate>")
+    }
+}
+
+// cycle
+pub static SYMBOL_CYCLE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("cycle"),
+    meta: Meta {
+        doc: Cow::Borrowed("Infinite generator repeating the values: (cycle [1 2]) => 1 2 1 2 ..."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("cycle"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleFn;
+
+impl Function for CycleFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        // the values of the first pass are kept to repeat them
+        let mut cursor = Some(Cursor::new(args[0].clone())?);
+        let mut seen = vec![];
+        let mut index = 0;
+        Ok(lazy("cycle", move || {
+            if let Some(c) = cursor.as_mut() {
+                match c.next_value()? {
+                    Some(v) => {
+                        seen.push(v.clone());
+                        return Ok(Some(v));
+                    }
+                    None => cursor = None,
+                }
+            }
+            if seen.is_empty() {
+                return Ok(None);
+            }
+            let v = seen[index % seen.len()].clone();
+            index += 1;
+            Ok(Some(v))
+        }))
+    }
+}
+
+impl fmt::Display for CycleFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: cycle>")
+    }
+}
+
+// zip
+pub static SYMBOL_ZIP: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("zip"),
+    meta: Meta {
+        doc: Cow::Borrowed("Lazily pair up values until the shortest ends: (zip [1 2] (range)) => [1 0] [2 1]."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("zip"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipFn;
+
+impl Function for ZipFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let mut cursors = args.into_iter().map(Cursor::new).collect::<Result<Vec<Cursor>>>()?;
+        Ok(lazy("zip", move || {
+            let mut values = vec![];
+            for cursor in cursors.iter_mut() {
+                match cursor.next_value()? {
+                    Some(v) => values.push(v),
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(Value::Vector(Vector::from(values))))
+        }))
+    }
+}
+
+impl fmt::Display for ZipFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: zip>")
+    }
+}
+
+// enumerate
+pub static SYMBOL_ENUMERATE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("enumerate"),
+    meta: Meta {
+        doc: Cow::Borrowed("Lazily pair values with their index: (enumerate [:a :b]) => [0 :a] [1 :b]."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("enumerate"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumerateFn;
+
+impl Function for EnumerateFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let mut cursor = Cursor::new(args[0].clone())?;
+        let mut index = 0;
+        Ok(lazy("enumerate", move || match cursor.next_value()? {
+            Some(v) => {
+                index += 1;
+                Ok(Some(Value::Vector(Vector::from(vec![Value::I64(index - 1), v]))))
+            }
+            None => Ok(None),
+        }))
+    }
+}
+
+impl fmt::Display for EnumerateFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: enumerate>")
+    }
+}
+
+// chain
+pub static SYMBOL_CHAIN: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("chain"),
+    meta: Meta {
+        doc: Cow::Borrowed("Lazily concatenate iterables: (chain [1 2] (range 3 5)) => 1 2 3 4."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("chain"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainFn;
+
+impl Function for ChainFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let mut cursors = args.into_iter().map(Cursor::new).collect::<Result<Vec<Cursor>>>()?;
+        cursors.reverse();
+        Ok(lazy("chain", move || {
+            while let Some(cursor) = cursors.last_mut() {
+                match cursor.next_value()? {
+                    Some(v) => return Ok(Some(v)),
+                    None => {
+                        cursors.pop();
+                    }
+                }
+            }
+            Ok(None)
+        }))
+    }
+}
+
+impl fmt::Display for ChainFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: chain>")
+    }
+}
+
+// collect
+pub static SYMBOL_COLLECT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("collect"),
+    meta: Meta {
+        doc: Cow::Borrowed("Materialize the values of a generator into a vector: (collect (take 3 (range)))."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("collect"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectFn;
+
+impl Function for CollectFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Value::as_vector(Cursor::new(args[0].clone())?.collect_values()?)
+    }
+}
+
+impl fmt::Display for CollectFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: collect>")
+    }
+}
+
+// yield
+pub static SYMBOL_YIELD: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("yield"),
+    meta: Meta {
+        doc: Cow::Borrowed("Produce the next value of the generator being run and suspend it until asked again."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("yield"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YieldFn;
+
+impl Function for YieldFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() > 1 {
+            return Err(arity_error_range(0, 1, args.len()));
+        }
+
+        yield_value(args.into_iter().next().unwrap_or(Value::Nil))
+    }
+}
+
+impl fmt::Display for YieldFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: yield>")
+    }
+}
+
+fn atom_argument(value: &Value) -> Result<Rc<Atom>> {
+    match value {
+        Value::Atom(a) => Ok(a.clone()),
+        _ => Err(type_error("atom", value.type_name().as_str())),
+    }
+}
+
+// atom
+pub static SYMBOL_ATOM: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("atom"),
+    meta: Meta {
+        doc: Cow::Borrowed("Create a mutable reference shared by everything holding it: (def counter (atom 0))."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("atom"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomFn;
+
+impl Function for AtomFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Ok(Value::Atom(Atom::new(args[0].clone())))
+    }
+}
+
+impl fmt::Display for AtomFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: atom>")
+    }
+}
+
+// deref
+pub static SYMBOL_DEREF: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("deref"),
+    meta: Meta {
+        doc: Cow::Borrowed("Get the current value of an atom, also written @a."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("deref"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerefFn;
+
+impl Function for DerefFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Ok(atom_argument(&args[0])?.deref())
+    }
+}
+
+impl fmt::Display for DerefFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: deref>")
+    }
+}
+
+// swap!
+pub static SYMBOL_SWAPE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("swap!"),
+    meta: Meta {
+        doc: Cow::Borrowed("Set an atom to (f current args...) and return the new value: (swap! counter + 1)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("swap!"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapEFn;
+
+impl Function for SwapEFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
+        }
+
+        let atom = atom_argument(&args[0])?;
+        let f = function_argument(&args[1])?;
+        atom.swap(&f, args[2..].to_vec())
+    }
+}
+
+impl fmt::Display for SwapEFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: swap!>")
+    }
+}
+
+// reset!
+pub static SYMBOL_RESETE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("reset!"),
+    meta: Meta {
+        doc: Cow::Borrowed("Set an atom to a value and return it."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("reset!"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResetEFn;
+
+impl Function for ResetEFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        atom_argument(&args[0])?.reset(args[1].clone())
+    }
+}
+
+impl fmt::Display for ResetEFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: reset!>")
+    }
+}
+
+// compare-and-set!
+pub static SYMBOL_COMPARE_AND_SETE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("compare-and-set!"),
+    meta: Meta {
+        doc: Cow::Borrowed("Set an atom to new only if its value equals old, return whether it was set."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("compare-and-set!"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareAndSetEFn;
+
+impl Function for CompareAndSetEFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 3 {
+            return Err(arity_error(3, args.len()));
+        }
+
+        let set = atom_argument(&args[0])?.compare_and_set(&args[1], args[2].clone())?;
+        Ok(Value::Bool(set))
+    }
+}
+
+impl fmt::Display for CompareAndSetEFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: compare-and-set!>")
+    }
+}
+
+// add-watch
+pub static SYMBOL_ADD_WATCH: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("add-watch"),
+    meta: Meta {
+        doc: Cow::Borrowed("Call (f key atom old new) after each change of an atom, a watch with the same key is replaced."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("add-watch"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddWatchFn;
+
+impl Function for AddWatchFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 3 {
+            return Err(arity_error(3, args.len()));
+        }
+
+        let atom = atom_argument(&args[0])?;
+        atom.add_watch(args[1].clone(), function_argument(&args[2])?);
+        Ok(args[0].clone())
+    }
+}
+
+impl fmt::Display for AddWatchFn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<builtin function: rest>")
+        write!(f, "<builtin function: add-watch>")
     }
 }
 
-// range
-pub static SYMBOL_RANGE: Lazy<Symbol> = Lazy::new(|| Symbol {
-    name: Cow::Borrowed("range"),
+// remove-watch
+pub static SYMBOL_REMOVE_WATCH: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("remove-watch"),
     meta: Meta {
-        doc: Cow::Borrowed("Create a range of i64."),
+        doc: Cow::Borrowed("Remove the watch of an atom added with the key, return whether there was one."),
         mutable: false,
+        arglists: vec![],
     },
-    hash: fxhash::hash("range"),
+    hash: fxhash::hash("remove-watch"),
 });
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct RangeFn;
+pub struct RemoveWatchFn;
 
-impl Function for RangeFn {
+impl Function for RemoveWatchFn {
     fn call(&self, args: Vec<Value>) -> Result<Value> {
-        if args.is_empty() || args.len() > 3 {
-            return Err(arity_error_range(1, 3, args.len()));
-        }
-
-        let mut start = 0;
-        let mut end = 0;
-        let mut step = 1;
-
-        if args.len() == 1 {
-            end = match args[0] {
-                Value::I64(i) => i,
-                _ => return Err(type_error("i64", args[0].type_name().as_str())),
-            };
-        } else if args.len() == 2 {
-            start = match args[0] {
-                Value::I64(i) => i,
-                _ => return Err(type_error("i64", args[0].type_name().as_str())),
-            };
-            end = match args[1] {
-                Value::I64(i) => i,
-                _ => return Err(type_error("i64", args[1].type_name().as_str())),
-            };
-        } else if args.len() == 3 {
-            start = match args[0] {
-                Value::I64(i) => i,
-                _ => return Err(type_error("i64", args[0].type_name().as_str())),
-            };
-            end = match args[1] {
-                Value::I64(i) => i,
-                _ => return Err(type_error("i64", args[1].type_name().as_str())),
-            };
-            step = match args[2] {
-                Value::I64(i) => i,
-                _ => return Err(type_error("i64", args[2].type_name().as_str())),
-            };
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
         }
 
-        Ok(Value::Generator(Rc::new(RefCell::new(Range::new(start, end, step)))))
+        Ok(Value::Bool(atom_argument(&args[0])?.remove_watch(&args[1])))
     }
 }
 
-impl fmt::Display for RangeFn {
+impl fmt::Display for RemoveWatchFn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<builtin function: range>")
+        write!(f, "<builtin function: remove-watch>")
     }
 }
 
@@ -1710,6 +2466,7 @@ pub static SYMBOL_SQRT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the square root of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("sqrt"),
 });
@@ -1743,6 +2500,7 @@ pub static SYMBOL_ABS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the absolute value of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("abs"),
 });
@@ -1776,6 +2534,7 @@ pub static SYMBOL_COS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the cosine of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("cos"),
 });
@@ -1809,6 +2568,7 @@ pub static SYMBOL_SIN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the sine of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("sin"),
 });
@@ -1842,6 +2602,7 @@ pub static SYMBOL_TAN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the tangent of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("tan"),
 });
@@ -1875,6 +2636,7 @@ pub static SYMBOL_ACOS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the arccosine of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("acos"),
 });
@@ -1908,6 +2670,7 @@ pub static SYMBOL_ASIN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the arcsine of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("asin"),
 });
@@ -1941,6 +2704,7 @@ pub static SYMBOL_ATAN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the arctangent of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("atan"),
 });
@@ -1974,6 +2738,7 @@ pub static SYMBOL_LOG: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the natural logarithm of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("log"),
 });
@@ -2013,6 +2778,7 @@ pub static SYMBOL_LN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the natural logarithm of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("ln"),
 });
@@ -2046,6 +2812,7 @@ pub static SYMBOL_LOG10: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the base 10 logarithm of a number."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("log10"),
 });
@@ -2079,6 +2846,7 @@ pub static SYMBOL_RAND: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get a random number between 0 and 1."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("rand"),
 });
@@ -2108,6 +2876,7 @@ pub static SYMBOL_RANDINT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get a random integer between two numbers."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("randint"),
 });
@@ -2147,6 +2916,7 @@ pub static SYMBOL_LEN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the length of a sequence."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("len"),
 });
@@ -2166,6 +2936,9 @@ impl Function for LenFn {
             Value::Map(m) => Ok(Value::I64(m.value.len() as i64)),
             Value::Set(s) => Ok(Value::I64(s.value.len() as i64)),
             Value::String(s) => Ok(Value::I64(s.len() as i64)),
+            Value::Generator(gen) if is_lazy(&gen) => {
+                Err(Error::Type(format!("len: {} has no length, collect it first", args[0])))
+            }
             Value::Generator(gen) => Ok(Value::I64(gen.borrow().len() as i64)),
             _ => Err(type_error("list, vector, map, set, or string", args[0].type_name().as_str())),
         }
@@ -2184,6 +2957,7 @@ pub static SYMBOL_JOIN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Join a sequence of values with a separator."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("join"),
 });
@@ -2226,6 +3000,7 @@ pub static SYMBOL_SPLIT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Split a string into a list of strings."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("split"),
 });
@@ -2279,6 +3054,7 @@ pub static SYMBOL_REPLACE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Replace all instances of a substring with another substring."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("replace"),
 });
@@ -2327,6 +3103,7 @@ pub static SYMBOL_CONCAT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Concatenate two strings, vector, or list"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("concat"),
 });
@@ -2383,6 +3160,7 @@ pub static SYMBOL_TRIM: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Trim whitespace from the beginning and end of a string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("trim"),
 });
@@ -2415,6 +3193,7 @@ pub static SYMBOL_INQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Check if a value is in a collection."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("in?"),
 });
@@ -2457,6 +3236,7 @@ pub static SYMBOL_FIND: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Find the first occurrence of a string/regex in a string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("find"),
 });
@@ -2507,6 +3287,7 @@ pub static SYMBOL_COUNT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Count the number of occurrences of a string/regex in a string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("count"),
 });
@@ -2561,6 +3342,7 @@ pub static SYMBOL_UPPER_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to uppercase."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("upper-case"),
 });
@@ -2593,6 +3375,7 @@ pub static SYMBOL_LOWER_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to lowercase."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("lower-case"),
 });
@@ -2625,6 +3408,7 @@ pub static SYMBOL_INDEX: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the index of given value in a list or vector or string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("index"),
 });
@@ -2680,6 +3464,7 @@ pub static SYMBOL_LOWER_CAMEL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to lower camel case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("lower-camel"),
 });
@@ -2712,6 +3497,7 @@ pub static SYMBOL_UPPER_CAMEL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to upper camel case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("upper-camel"),
 });
@@ -2744,6 +3530,7 @@ pub static SYMBOL_SNAKE_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to snake case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("snake-case"),
 });
@@ -2776,6 +3563,7 @@ pub static SYMBOL_KEBAB_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to kebab case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("kebab-case"),
 });
@@ -2808,6 +3596,7 @@ pub static SYMBOL_TITLE_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to title case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("title-case"),
 });
@@ -2840,6 +3629,7 @@ pub static SYMBOL_TRAIN_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to train case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("train-case"),
 });
@@ -2872,6 +3662,7 @@ pub static SYMBOL_SHOUTY_SNAKE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to shouty snake case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("shouty-snake"),
 });
@@ -2904,6 +3695,7 @@ pub static SYMBOL_SHOUTY_KEBAB: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Convert a string to shouty kebab case."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("shouty-kebab"),
 });
@@ -2936,6 +3728,7 @@ pub static SYMBOL_REPEAT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Repeat a string a given number of times."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("repeat"),
 });
@@ -2973,6 +3766,7 @@ pub static SYMBOL_FIND_ALL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Find all occurrences of a string/regex in a string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("find-all"),
 });
@@ -3023,6 +3817,7 @@ pub static SYMBOL_REVERSE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Reverse a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("reverse"),
 });
@@ -3065,6 +3860,7 @@ pub static SYMBOL_LAST: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the last element of a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("last"),
 });
@@ -3100,12 +3896,17 @@ impl Function for LastFn {
                     Ok(Value::Nil)
                 }
             }
-            Value::Generator(gen) => {
-                if let Some(v) = gen.borrow().at((gen.borrow().len() - 1) as i64) {
-                    Ok(v.clone())
-                } else {
-                    Ok(Value::Nil)
+            Value::Generator(gen) if is_lazy(&gen) => {
+                let mut last = Value::Nil;
+                let mut cursor = Cursor::new(Value::Generator(gen))?;
+                while let Some(v) = cursor.next_value()? {
+                    last = v;
                 }
+                Ok(last)
+            }
+            Value::Generator(gen) => {
+                let len = gen.borrow().len() as i64;
+                Ok(gen.borrow().at(len - 1).unwrap_or(Value::Nil))
             }
             _ => Err(type_error("list, vector, string or generator", args[0].type_name().as_str())),
         }
@@ -3124,6 +3925,7 @@ pub static SYMBOL_SUM: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the sum"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("sum"),
 });
@@ -3183,13 +3985,12 @@ impl Function for SumFn {
             }
             Value::Generator(gen) => {
                 let mut sum = 0.0;
-                let length = gen.borrow().len();
-                for i in 0..length {
-                    match gen.borrow().at(i as i64) {
-                        Some(Value::I64(i)) => sum += i as f64,
-                        Some(Value::F64(f)) => sum += f,
-                        None => break,
-                        v => return Err(type_error("i64 or f64", v.unwrap().type_name().as_str())),
+                let mut cursor = Cursor::new(Value::Generator(gen))?;
+                while let Some(v) = cursor.next_value()? {
+                    match v {
+                        Value::I64(i) => sum += i as f64,
+                        Value::F64(f) => sum += f,
+                        _ => return Err(type_error("i64 or f64", v.type_name().as_str())),
                     }
                 }
                 Ok(Value::F64(sum))
@@ -3214,6 +4015,7 @@ pub static SYMBOL_MEAN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the mean"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("mean"),
 });
@@ -3277,14 +4079,15 @@ impl Function for MeanFn {
             }
             Value::Generator(gen) => {
                 let mut sum = 0.0;
-                let length = gen.borrow().len();
-                for i in 0..length {
-                    match gen.borrow().at(i as i64) {
-                        Some(Value::I64(i)) => sum += i as f64,
-                        Some(Value::F64(f)) => sum += f,
-                        None => break,
-                        v => return Err(type_error("i64 or f64", v.unwrap().type_name().as_str())),
+                let mut length = 0;
+                let mut cursor = Cursor::new(Value::Generator(gen))?;
+                while let Some(v) = cursor.next_value()? {
+                    match v {
+                        Value::I64(i) => sum += i as f64,
+                        Value::F64(f) => sum += f,
+                        _ => return Err(type_error("i64 or f64", v.type_name().as_str())),
                     }
+                    length += 1;
                 }
                 Ok(Value::F64(sum / length as f64))
             }
@@ -3340,6 +4143,7 @@ pub static SYMBOL_MAX: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the maximum value in a list or vector of numbers."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("max"),
 });
@@ -3363,12 +4167,7 @@ impl Function for MaxFn {
                 let keys = s.value.iter().cloned().collect::<Vec<Value>>();
                 helper_max(keys)
             }
-            Value::Generator(gen) => {
-                let vals = (0..gen.borrow().len())
-                    .map(|i| gen.borrow().at(i as i64).unwrap())
-                    .collect::<Vec<Value>>();
-                helper_max(vals)
-            }
+            Value::Generator(gen) => helper_max(Cursor::new(Value::Generator(gen))?.collect_values()?),
             _ => Err(type_error("list or vector", args[0].type_name().as_str())),
         }
     }
@@ -3386,6 +4185,7 @@ pub static SYMBOL_MIN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the minimum value in a list or vector of numbers."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("min"),
 });
@@ -3409,12 +4209,7 @@ impl Function for MinFn {
                 let keys = s.value.iter().cloned().collect::<Vec<Value>>();
                 helper_min(keys)
             }
-            Value::Generator(gen) => {
-                let vals = (0..gen.borrow().len())
-                    .map(|i| gen.borrow().at(i as i64).unwrap())
-                    .collect::<Vec<Value>>();
-                helper_min(vals)
-            }
+            Value::Generator(gen) => helper_min(Cursor::new(Value::Generator(gen))?.collect_values()?),
             _ => Err(type_error("list or vector", args[0].type_name().as_str())),
         }
     }
@@ -3432,6 +4227,7 @@ pub static SYMBOL_INDEX_ALL: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the indices of all occurrences of a value in a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("index-all"),
 });
@@ -3481,6 +4277,7 @@ pub static SYMBOL_SOMEQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Check if a predicate is true for some value in a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("some?"),
 });
@@ -3528,6 +4325,7 @@ pub static SYMBOL_EVERYQ: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Check if a predicate is true for every value in a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("every?"),
 });
@@ -3601,6 +4399,7 @@ pub static SYMBOL_SORT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Sort a list or vector or string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("sort"),
 });
@@ -3659,6 +4458,7 @@ pub static SYMBOL_SHUFFLE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Shuffle a list or vector or string."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("shuffle"),
 });
@@ -3705,6 +4505,7 @@ pub static SYMBOL_PUSH: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Push a value to the end of a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("push"),
 });
@@ -3747,6 +4548,7 @@ pub static SYMBOL_CONS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Prepend a value to the beginning of a list or vector."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("cons"),
 });
@@ -3789,6 +4591,7 @@ pub static SYMBOL_KEYS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the keys of a map."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("keys"),
 });
@@ -3826,6 +4629,7 @@ pub static SYMBOL_VALS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the values of a map."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("vals"),
 });
@@ -3863,6 +4667,7 @@ pub static SYMBOL_ITEMS: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the items of a map."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("items"),
 });
@@ -3900,6 +4705,7 @@ pub static SYMBOL_GET: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get a value from a map."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("get"),
 });
@@ -3991,8 +4797,9 @@ impl fmt::Display for GetFn {
 pub static SYMBOL_UNION: Lazy<Symbol> = Lazy::new(|| Symbol {
     name: Cow::Borrowed("union"),
     meta: Meta {
-        doc: Cow::Borrowed("Get the union of two sets."),
+        doc: Cow::Borrowed("Get the union of two sets, or the union type of types: (union #i64 #str)."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("union"),
 });
@@ -4002,6 +4809,17 @@ pub struct UnionFn;
 
 impl Function for UnionFn {
     fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if let Some(Value::Type(_)) = args.first() {
+            let types = args
+                .iter()
+                .map(|arg| match arg {
+                    Value::Type(t) => Ok(t.clone()),
+                    _ => Err(type_error("type", arg.type_name().as_str())),
+                })
+                .collect::<Result<Vec<Type>>>()?;
+            return Ok(Value::Type(Type::union(types)));
+        }
+
         if args.len() != 2 {
             return Err(arity_error_range(2, 2, args.len()));
         }
@@ -4027,6 +4845,7 @@ pub static SYMBOL_INTERSECT: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the intersection of two sets."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("intersect"),
 });
@@ -4061,6 +4880,7 @@ pub static SYMBOL_DIFFERENCE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the difference of two sets."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("difference"),
 });
@@ -4095,6 +4915,7 @@ pub static SYMBOL_APPLY: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Apply a function to a sequence"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("apply"),
 });
@@ -4126,13 +4947,7 @@ impl Function for ApplyFn {
                     }
                     f.call(result)
                 }
-                Value::Generator(gen) => {
-                    let mut result = vec![];
-                    for i in 0..gen.borrow().len() {
-                        result.push(gen.borrow().at(i as i64).unwrap());
-                    }
-                    f.call(result)
-                }
+                Value::Generator(gen) => f.call(Cursor::new(Value::Generator(gen))?.collect_values()?),
                 _ => Err(type_error(
                     "list, vector, map, set or generator",
                     args[1].type_name().as_str(),
@@ -4155,6 +4970,7 @@ pub static SYMBOL_MAP: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Map a function over a sequence"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("map"),
 });
@@ -4198,12 +5014,13 @@ impl Function for MapFn {
                     }
                     Value::as_vector(result)
                 }
+                // lazy for generators
                 Value::Generator(gen) => {
-                    let mut result = vec![];
-                    for i in 0..gen.borrow().len() {
-                        result.push(f.call(vec![gen.borrow().at(i as i64).unwrap()])?);
-                    }
-                    Value::as_vector(result)
+                    let mut cursor = Cursor::new(Value::Generator(gen))?;
+                    Ok(lazy("map", move || match cursor.next_value()? {
+                        Some(v) => f.call(vec![v]).map(Some),
+                        None => Ok(None),
+                    }))
                 }
                 _ => Err(type_error(
                     "list, vector, map, set or generator",
@@ -4227,6 +5044,7 @@ pub static SYMBOL_FILTER: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Filter a sequence by a predicate"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("filter"),
 });
@@ -4278,15 +5096,17 @@ impl Function for FilterFn {
                     }
                     Value::as_set(result)
                 }
+                // lazy for generators
                 Value::Generator(gen) => {
-                    let mut result = vec![];
-                    for i in 0..gen.borrow().len() {
-                        let v = gen.borrow().at(i as i64).unwrap();
-                        if f.call(vec![v.clone()])?.is_truthy() {
-                            result.push(v);
+                    let mut cursor = Cursor::new(Value::Generator(gen))?;
+                    Ok(lazy("filter", move || {
+                        while let Some(v) = cursor.next_value()? {
+                            if f.call(vec![v.clone()])?.is_truthy() {
+                                return Ok(Some(v));
+                            }
                         }
-                    }
-                    Value::as_vector(result)
+                        Ok(None)
+                    }))
                 }
                 _ => Err(type_error(
                     "list, vector, map, set or generator",
@@ -4310,6 +5130,7 @@ pub static SYMBOL_REDUCE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Reduce a sequence to a single value"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("reduce"),
 });
@@ -4355,8 +5176,8 @@ impl Function for ReduceFn {
                 }
                 Value::Generator(gen) => {
                     let mut result = args[1].clone();
-                    for i in 0..gen.borrow().len() {
-                        let v = gen.borrow().at(i as i64).unwrap();
+                    let mut cursor = Cursor::new(Value::Generator(gen))?;
+                    while let Some(v) = cursor.next_value()? {
                         result = f.call(vec![result, v])?;
                     }
                     Ok(result)
@@ -4376,3 +5197,300 @@ impl fmt::Display for ReduceFn {
         write!(f, "<builtin function reduce>")
     }
 }
+
+fn function_arguments(values: &[Value]) -> Result<Vec<Rc<dyn Function>>> {
+    values.iter().map(function_argument).collect()
+}
+
+// partial
+pub static SYMBOL_PARTIAL: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("partial"),
+    meta: Meta {
+        doc: Cow::Borrowed("Fix the first arguments of a function: ((partial * 10) 1) => 10."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("partial"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialFn;
+
+impl Function for PartialFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        Ok(Value::Function(Rc::new(Partial {
+            function: function_argument(&args[0])?,
+            args: args[1..].to_vec(),
+        })))
+    }
+}
+
+impl fmt::Display for PartialFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: partial>")
+    }
+}
+
+// comp
+pub static SYMBOL_COMP: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("comp"),
+    meta: Meta {
+        doc: Cow::Borrowed("Compose functions from right to left: ((comp str +) 7 8 9) => \"24\"."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("comp"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompFn;
+
+impl Function for CompFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Ok(Value::Function(Rc::new(IdentityFn)));
+        }
+
+        Ok(Value::Function(Rc::new(Comp {
+            functions: function_arguments(&args)?,
+        })))
+    }
+}
+
+impl fmt::Display for CompFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: comp>")
+    }
+}
+
+// juxt
+pub static SYMBOL_JUXT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("juxt"),
+    meta: Meta {
+        doc: Cow::Borrowed("Call each function with the same arguments: ((juxt min max) [3 1 2]) => [1, 3]."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("juxt"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JuxtFn;
+
+impl Function for JuxtFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        Ok(Value::Function(Rc::new(Juxt {
+            functions: function_arguments(&args)?,
+        })))
+    }
+}
+
+impl fmt::Display for JuxtFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: juxt>")
+    }
+}
+
+// complement
+pub static SYMBOL_COMPLEMENT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("complement"),
+    meta: Meta {
+        doc: Cow::Borrowed("Negate a predicate: ((complement even?) 1) => true."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("complement"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplementFn;
+
+impl Function for ComplementFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Ok(Value::Function(Rc::new(Complement {
+            function: function_argument(&args[0])?,
+        })))
+    }
+}
+
+impl fmt::Display for ComplementFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: complement>")
+    }
+}
+
+// constantly
+pub static SYMBOL_CONSTANTLY: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("constantly"),
+    meta: Meta {
+        doc: Cow::Borrowed("Make a function that ignores its arguments and returns the value."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("constantly"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantlyFn;
+
+impl Function for ConstantlyFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Ok(Value::Function(Rc::new(Constantly { value: args[0].clone() })))
+    }
+}
+
+impl fmt::Display for ConstantlyFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: constantly>")
+    }
+}
+
+// identity
+pub static SYMBOL_IDENTITY: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("identity"),
+    meta: Meta {
+        doc: Cow::Borrowed("Return the argument."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("identity"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityFn;
+
+impl Function for IdentityFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        Ok(args[0].clone())
+    }
+}
+
+impl fmt::Display for IdentityFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: identity>")
+    }
+}
+
+// fnil
+pub static SYMBOL_FNIL: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("fnil"),
+    meta: Meta {
+        doc: Cow::Borrowed("Replace nil arguments with defaults: ((fnil inc 0) nil) => 1."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("fnil"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnilFn;
+
+impl Function for FnilFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
+        }
+
+        Ok(Value::Function(Rc::new(Fnil {
+            function: function_argument(&args[0])?,
+            defaults: args[1..].to_vec(),
+        })))
+    }
+}
+
+impl fmt::Display for FnilFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: fnil>")
+    }
+}
+
+// memoize
+pub static SYMBOL_MEMOIZE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("memoize"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Cache the results of a function by its arguments, (memoize f 100) keeps only the 100 most recently used.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("memoize"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoizeFn;
+
+impl Function for MemoizeFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(arity_error_range(1, 2, args.len()));
+        }
+
+        let capacity = match args.get(1) {
+            Some(value) => match NonZeroUsize::new(count_argument(value)?) {
+                Some(capacity) => Some(capacity),
+                None => return Err(Error::Value("memoize: capacity must be positive".to_string())),
+            },
+            None => None,
+        };
+        Ok(Value::Function(Rc::new(Memoized::new(
+            function_argument(&args[0])?,
+            capacity,
+        ))))
+    }
+}
+
+impl fmt::Display for MemoizeFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: memoize>")
+    }
+}
+
+// exception constructors (TypeError, KeyError, ..., Exception)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExceptionFn {
+    pub name: &'static str,
+}
+
+impl Function for ExceptionFn {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() > 2 {
+            return Err(arity_error_range(0, 2, args.len()));
+        }
+
+        let message = match args.first() {
+            None => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(v) => return Err(type_error("string", v.type_name().as_str())),
+        };
+        let data = args.get(1).cloned().unwrap_or(Value::Nil);
+
+        Ok(exception_value(self.name, message, data))
+    }
+}
+
+impl fmt::Display for ExceptionFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin function: {}>", self.name)
+    }
+}
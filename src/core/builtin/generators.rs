@@ -1,8 +1,16 @@
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
 
+use corosensei::stack::DefaultStack;
+use corosensei::{Coroutine, CoroutineResult, Yielder};
+
+use crate::core::eval::{generator_eval_depth, swap_eval_depth};
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
+use crate::core::types::function::Function;
 use crate::core::types::generator::Generator;
+use crate::core::types::lambda::{arity_mismatch_error, select_arity, Lambda};
 use crate::core::types::sliceable::Sliceable;
 use crate::core::types::vector::Vector;
 use crate::core::value::Value;
@@ -107,10 +115,12 @@ impl DoubleEndedIterator for Range {
 
 impl Sliceable for Range {
     fn len(&self) -> usize {
-        if self.step == 0 {
-            0
-        } else {
-            ((self.end - self.start) / self.step) as usize
+        // the last value may be before end: (range 0 10 3) => 0 3 6 9
+        match self.step {
+            0 => 0,
+            step if step > 0 && self.end > self.start => ((self.end - self.start + step - 1) / step) as usize,
+            step if step < 0 && self.end < self.start => ((self.start - self.end - step - 1) / -step) as usize,
+            _ => 0,
         }
     }
     fn at(&self, index: i64) -> Option<Value> {
@@ -180,3 +190,222 @@ impl Sliceable for Range {
 }
 
 impl Generator for Range {}
+
+// lazy generator: each value is produced when it is asked for (map, filter, take, ... of generators)
+type NextValue = Rc<RefCell<dyn FnMut() -> Result<Option<Value>>>>;
+
+#[derive(Clone)]
+pub struct LazyGenerator {
+    name: &'static str,
+    next: NextValue,
+}
+
+impl LazyGenerator {
+    pub fn new(name: &'static str, next: impl FnMut() -> Result<Option<Value>> + 'static) -> Self {
+        LazyGenerator {
+            name,
+            next: Rc::new(RefCell::new(next)),
+        }
+    }
+}
+
+impl fmt::Debug for LazyGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Lazy({})", self.name)
+    }
+}
+
+impl fmt::Display for LazyGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<generator: {} >", self.name)
+    }
+}
+
+// errors are reported by try_next
+impl Iterator for LazyGenerator {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_next().ok().flatten()
+    }
+}
+
+impl Sliceable for LazyGenerator {
+    fn len(&self) -> usize {
+        0
+    }
+    fn at(&self, _index: i64) -> Option<Value> {
+        None
+    }
+    fn slice(&self, _start: Option<i64>, _end: Option<i64>, _step: Option<i64>) -> Result<Value> {
+        Err(Error::Type(format!(
+            "cannot slice a lazy generator ({}), collect it first",
+            self.name
+        )))
+    }
+}
+
+impl Generator for LazyGenerator {
+    fn try_next(&mut self) -> Result<Option<Value>> {
+        (self.next.borrow_mut())()
+    }
+
+    fn is_sliceable(&self) -> bool {
+        false
+    }
+}
+
+// user-defined generator: the body of defgen runs on its own stack and is suspended at each yield
+pub const GENERATOR_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+type Body = Coroutine<(), Value, Result<Value>, DefaultStack>;
+
+thread_local! {
+    // the yielders of the generator bodies being run, innermost last
+    static YIELDERS: RefCell<Vec<*const Yielder<(), Value>>> = const { RefCell::new(vec![]) };
+}
+
+// (yield x) in the body of a generator
+pub fn yield_value(value: Value) -> Result<Value> {
+    let yielder = match YIELDERS.with(|y| y.borrow_mut().pop()) {
+        Some(yielder) => yielder,
+        None => return Err(Error::Syntax("yield outside of a generator".to_string())),
+    };
+    // SAFETY: the yielder is pushed by a running coroutine and lives as long as its stack
+    unsafe { (*yielder).suspend(value) };
+    YIELDERS.with(|y| y.borrow_mut().push(yielder));
+    Ok(Value::Nil)
+}
+
+struct Suspended {
+    body: Body,
+    depth: usize, // eval depth of the body (see swap_eval_depth)
+}
+
+impl Suspended {
+    fn resume(&mut self) -> CoroutineResult<Value, Result<Value>> {
+        let outer = swap_eval_depth(self.depth);
+        let result = self.body.resume(());
+        self.depth = swap_eval_depth(outer);
+        result
+    }
+}
+
+// a generator dropped before it is exhausted unwinds its body with its own depth
+impl Drop for Suspended {
+    fn drop(&mut self) {
+        let outer = swap_eval_depth(self.depth);
+        self.body.force_unwind();
+        swap_eval_depth(outer);
+    }
+}
+
+#[derive(Clone)]
+pub struct CoroutineGenerator {
+    name: String,
+    body: Rc<RefCell<Option<Suspended>>>, // None when the body has returned
+}
+
+impl CoroutineGenerator {
+    pub fn new(name: String, function: Rc<Lambda>, args: Vec<Value>) -> Result<Self> {
+        let stack = DefaultStack::new(GENERATOR_STACK_SIZE).map_err(Error::IO)?;
+        let body = Coroutine::with_stack(stack, move |yielder: &Yielder<(), Value>, ()| {
+            YIELDERS.with(|y| y.borrow_mut().push(yielder));
+            let result = function.call(args);
+            YIELDERS.with(|y| y.borrow_mut().pop());
+            result
+        });
+        Ok(CoroutineGenerator {
+            name,
+            body: Rc::new(RefCell::new(Some(Suspended {
+                body,
+                depth: generator_eval_depth(),
+            }))),
+        })
+    }
+}
+
+impl fmt::Debug for CoroutineGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CoroutineGenerator({})", self.name)
+    }
+}
+
+impl fmt::Display for CoroutineGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<generator: {} >", self.name)
+    }
+}
+
+// errors are reported by try_next
+impl Iterator for CoroutineGenerator {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_next().ok().flatten()
+    }
+}
+
+impl Sliceable for CoroutineGenerator {
+    fn len(&self) -> usize {
+        0
+    }
+    fn at(&self, _index: i64) -> Option<Value> {
+        None
+    }
+    fn slice(&self, _start: Option<i64>, _end: Option<i64>, _step: Option<i64>) -> Result<Value> {
+        Err(Error::Type(format!(
+            "cannot slice a lazy generator ({}), collect it first",
+            self.name
+        )))
+    }
+}
+
+impl Generator for CoroutineGenerator {
+    fn try_next(&mut self) -> Result<Option<Value>> {
+        let mut body = match self.body.try_borrow_mut() {
+            Ok(body) => body,
+            Err(_) => return Err(Error::Value(format!("generator {} is already running", self.name))),
+        };
+        let result = match body.as_mut() {
+            Some(suspended) => suspended.resume(),
+            None => return Ok(None),
+        };
+        match result {
+            CoroutineResult::Yield(value) => Ok(Some(value)),
+            CoroutineResult::Return(result) => {
+                *body = None;
+                result.map(|_| None)
+            }
+        }
+    }
+
+    fn is_sliceable(&self) -> bool {
+        false
+    }
+}
+
+// (defgen name [params] body) binds a function returning a new generator for each call
+#[derive(Debug, Clone)]
+pub struct GeneratorFunction {
+    pub name: String,
+    pub function: Rc<Lambda>,
+}
+
+impl Function for GeneratorFunction {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        // arity and parameter types are checked on the call, not on the first value
+        match select_arity(&self.function.arities, args.len()) {
+            Some(arity) => arity.check_arguments(&args)?,
+            None => return Err(arity_mismatch_error(&self.function.arities, args.len())),
+        }
+        let generator = CoroutineGenerator::new(self.name.clone(), self.function.clone(), args)?;
+        Ok(Value::Generator(Rc::new(RefCell::new(generator))))
+    }
+}
+
+impl fmt::Display for GeneratorFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<generator function: {}>", self.name)
+    }
+}
@@ -0,0 +1,207 @@
+/* core/builtin/higher_order.rs */
+
+use std::cell::RefCell;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::rc::Rc;
+
+use lru::LruCache;
+
+use crate::core::types::error::Result;
+use crate::core::types::function::Function;
+use crate::core::value::Value;
+
+// functions made from other functions: (partial * 10), (comp str +), (memoize f), ...
+
+// (partial f a b) => (fn [& args] (f a b ...args))
+#[derive(Debug, Clone)]
+pub struct Partial {
+    pub function: Rc<dyn Function>,
+    pub args: Vec<Value>,
+}
+
+impl Function for Partial {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let mut all = self.args.clone();
+        all.extend(args);
+        self.function.call(all)
+    }
+}
+
+impl fmt::Display for Partial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: partial>")
+    }
+}
+
+// (comp f g h) => (fn [& args] (f (g (apply h args)))), at least one function
+#[derive(Debug, Clone)]
+pub struct Comp {
+    pub functions: Vec<Rc<dyn Function>>,
+}
+
+impl Function for Comp {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let (last, rest) = self.functions.split_last().expect("comp of no functions is identity");
+        let mut result = last.call(args)?;
+        for function in rest.iter().rev() {
+            result = function.call(vec![result])?;
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Comp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: comp>")
+    }
+}
+
+// (juxt f g) => (fn [& args] [(apply f args) (apply g args)])
+#[derive(Debug, Clone)]
+pub struct Juxt {
+    pub functions: Vec<Rc<dyn Function>>,
+}
+
+impl Function for Juxt {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let results = self
+            .functions
+            .iter()
+            .map(|f| f.call(args.clone()))
+            .collect::<Result<Vec<Value>>>()?;
+        Value::as_vector(results)
+    }
+}
+
+impl fmt::Display for Juxt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: juxt>")
+    }
+}
+
+// (complement even?) => (fn [& args] (not (apply even? args)))
+#[derive(Debug, Clone)]
+pub struct Complement {
+    pub function: Rc<dyn Function>,
+}
+
+impl Function for Complement {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        Ok(Value::Bool(!self.function.call(args)?.is_truthy()))
+    }
+}
+
+impl fmt::Display for Complement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: complement>")
+    }
+}
+
+// (constantly x) => (fn [& _] x)
+#[derive(Debug, Clone)]
+pub struct Constantly {
+    pub value: Value,
+}
+
+impl Function for Constantly {
+    fn call(&self, _args: Vec<Value>) -> Result<Value> {
+        Ok(self.value.clone())
+    }
+}
+
+impl fmt::Display for Constantly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: constantly>")
+    }
+}
+
+// (fnil f 0) => (fn [x & args] (apply f (if (nil? x) 0 x) args))
+#[derive(Debug, Clone)]
+pub struct Fnil {
+    pub function: Rc<dyn Function>,
+    pub defaults: Vec<Value>,
+}
+
+impl Function for Fnil {
+    fn call(&self, mut args: Vec<Value>) -> Result<Value> {
+        for (arg, default) in args.iter_mut().zip(&self.defaults) {
+            if *arg == Value::Nil {
+                *arg = default.clone();
+            }
+        }
+        self.function.call(args)
+    }
+}
+
+impl fmt::Display for Fnil {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: fnil>")
+    }
+}
+
+// (memoize f) caches every result, (memoize f 100) keeps the 100 most recently used
+#[derive(Debug, Clone)]
+pub struct Memoized {
+    pub function: Rc<dyn Function>,
+    pub cache: Rc<RefCell<LruCache<Arguments, Value>>>,
+}
+
+impl Memoized {
+    pub fn new(function: Rc<dyn Function>, capacity: Option<NonZeroUsize>) -> Self {
+        let cache = match capacity {
+            Some(capacity) => LruCache::new(capacity),
+            None => LruCache::unbounded(),
+        };
+        Memoized {
+            function,
+            cache: Rc::new(RefCell::new(cache)),
+        }
+    }
+}
+
+impl Function for Memoized {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let key = Arguments(args);
+        if let Some(value) = self.cache.borrow_mut().get(&key) {
+            return Ok(value.clone());
+        }
+        // the cache is not borrowed while f runs, a recursive f is memoized too
+        let value = self.function.call(key.0.clone())?;
+        self.cache.borrow_mut().put(key, value.clone());
+        Ok(value)
+    }
+}
+
+impl fmt::Display for Memoized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<function: memoize>")
+    }
+}
+
+// arguments as a cache key, equal when the values are equal (functions never are)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arguments(Vec<Value>);
+
+impl Hash for Arguments {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.iter().for_each(|value| hash_value(value, state));
+    }
+}
+
+// maps, sets and structs of equal contents may be ordered differently, only their kind and size are hashed
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    std::mem::discriminant(value).hash(state);
+    match value {
+        Value::Bool(b) => b.hash(state),
+        Value::I64(i) => i.hash(state),
+        Value::F64(f) => (if *f == 0.0 { 0.0 } else { *f }).to_bits().hash(state), // -0.0 == 0.0
+        Value::Symbol(_) | Value::Keyword(_) | Value::String(_) => value.hash(state),
+        Value::List(l) => l.value.iter().for_each(|v| hash_value(v, state)),
+        Value::Vector(v) => v.value.iter().for_each(|v| hash_value(v, state)),
+        Value::Map(m) => m.value.len().hash(state),
+        Value::Set(s) => s.value.len().hash(state),
+        _ => {}
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::ops::ControlFlow;
 use std::rc::Rc;
@@ -9,20 +10,35 @@ use std::vec;
 
 use once_cell::sync::Lazy;
 
+use crate::core::builtin::generators::GeneratorFunction;
+use crate::core::destructure::{bind_pattern, check_pattern, parse_params, split_annotations};
 use crate::core::environment::Environment;
-use crate::core::eval::eval;
+use crate::core::eval::{eval, eval_body_tail, eval_tail};
+use crate::core::module::{load, resolve};
+use crate::core::pattern::match_pattern;
+use crate::core::types::condition::{condition_matches, next_restart_id, signal, to_condition, Handler, Restart};
 use crate::core::types::error::index_out_of_range_error;
 use crate::core::types::error::key_not_found_error;
 use crate::core::types::error::type_error;
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
 use crate::core::types::error::{arity_error, arity_error_min, arity_error_range};
+use crate::core::types::function::Function;
+use crate::core::types::generator::Cursor;
+use crate::core::types::generic::{Generic, Method};
 use crate::core::types::keyword::Keyword;
-use crate::core::types::lambda::Lambda;
+use crate::core::types::lambda::{arglist, bind_arguments, finish_tail_call, typed_arglist, Arity, Lambda};
+use crate::core::types::map::Map;
 use crate::core::types::meta::Meta;
+use crate::core::types::protocol::{Protocol, ProtocolMethod};
+use crate::core::types::r#enum::{EnumType, Variant, VariantPredicate};
 use crate::core::types::r#macro::Macro;
+use crate::core::types::r#struct::{StructConstructor, StructType};
+use crate::core::types::r#type::Type;
 use crate::core::types::sliceable::SliceableMut;
 use crate::core::types::symbol::Symbol;
+use crate::core::types::type_name::{TypeName, BUILTIN_TYPE_NAMES};
+use crate::core::types::user_macro::UserMacro;
 use crate::core::types::vector::Vector;
 use crate::core::value::Value;
 
@@ -32,6 +48,7 @@ pub static SYMBOL_DEF: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Bind a value to a symbol."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("def"),
 });
@@ -41,6 +58,7 @@ pub struct DefMacro;
 
 impl Macro for DefMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let (args, annotation) = split_binding_annotation(args, &environment)?;
         if args.is_empty() || args.len() > 3 {
             return Err(arity_error_range(1, 3, args.len()));
         }
@@ -66,13 +84,30 @@ impl Macro for DefMacro {
         }
 
         let value = eval(body, environment.clone(), false)?;
+        if let Some(t) = &annotation {
+            t.check(&format!("binding {}", symbol), &value)?;
+        }
 
         environment.borrow_mut().insert(&symbol, value)?;
+        environment.borrow_mut().annotate(&symbol, annotation);
+        environment.borrow_mut().dynamics.remove(symbol.name.as_ref());
 
         Ok(Value::Symbol(symbol))
     }
 }
 
+// (def name #type value) => (def name value) and #type
+// (def name #type) binds the type itself
+fn split_binding_annotation(mut args: Vec<Value>, environment: &Rc<RefCell<Environment>>) -> Result<(Vec<Value>, Option<Type>)> {
+    match &args[..] {
+        [_, Value::Type(_), _, ..] => match args.remove(1) {
+            Value::Type(t) => Ok((args, Some(environment.borrow().resolve_type(&t)?))),
+            _ => unreachable!(),
+        },
+        _ => Ok((args, None)),
+    }
+}
+
 impl fmt::Display for DefMacro {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<builtin macro: def>")
@@ -85,6 +120,7 @@ pub static SYMBOL_CONST: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Bind a value to a symbol."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("const"),
 });
@@ -94,6 +130,7 @@ pub struct ConstMacro;
 
 impl Macro for ConstMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let (args, annotation) = split_binding_annotation(args, &environment)?;
         if args.is_empty() || args.len() > 3 {
             return Err(arity_error_range(1, 3, args.len()));
         }
@@ -119,6 +156,9 @@ impl Macro for ConstMacro {
         }
 
         let value = eval(body, environment.clone(), false)?;
+        if let Some(t) = &annotation {
+            t.check(&format!("binding {}", symbol), &value)?;
+        }
 
         symbol.meta.mutable = false;
 
@@ -134,12 +174,43 @@ impl fmt::Display for ConstMacro {
     }
 }
 
+// def-dynamic
+pub static SYMBOL_DEF_DYNAMIC: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("def-dynamic"),
+    meta: Meta {
+        doc: Cow::Borrowed("Bind a value to a symbol that can be rebound by binding: (def-dynamic *precision* 6)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("def-dynamic"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefDynamicMacro;
+
+impl Macro for DefDynamicMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let symbol = DefMacro.call(args, environment.clone())?;
+        if let Value::Symbol(sym) = &symbol {
+            environment.borrow_mut().dynamics.insert(sym.name.to_string());
+        }
+        Ok(symbol)
+    }
+}
+
+impl fmt::Display for DefDynamicMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: def-dynamic>")
+    }
+}
+
 // set!
 pub static SYMBOL_SETE: Lazy<Symbol> = Lazy::new(|| Symbol {
     name: Cow::Borrowed("set!"),
     meta: Meta {
         doc: Cow::Borrowed("Bind a value to a symbol."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("set!"),
 });
@@ -153,6 +224,27 @@ impl Macro for SetEMacro {
             return Err(arity_error(2, args.len()));
         }
 
+        // (set! (:field instance) value) or (set! instance.field value)
+        if let Value::List(place) = &args[0] {
+            let place = match &place.value[..] {
+                [Value::Symbol(dot), target, key] if *dot == *SYMBOL_DOT => match key {
+                    Value::List(call) if call.value.len() == 1 => Some((&call.value[0], target)),
+                    key => Some((key, target)),
+                },
+                [key, target] => Some((key, target)),
+                _ => None,
+            };
+            if let Some((Value::Keyword(key), target)) = place {
+                let instance = match eval(target.clone(), environment.clone(), false)? {
+                    Value::Struct(s) => s,
+                    v => return Err(type_error("struct", v.type_name().as_str())),
+                };
+                let value = eval(args[1].clone(), environment.clone(), false)?;
+                instance.borrow_mut().set_field(key, value)?;
+                return Ok(Value::Keyword(key.clone()));
+            }
+        }
+
         let symbol = match args[0].clone() {
             Value::Symbol(sym) => sym,
             _ => return Err(Error::Type("set: first argument must be a symbol".to_string())),
@@ -177,6 +269,7 @@ pub static SYMBOL_LET: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Bind a value to a symbol in a local scope."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("let"),
 });
@@ -186,6 +279,24 @@ pub struct LetMacro;
 
 impl Macro for LetMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let local_env = self.bind(&args, environment)?;
+
+        let mut result = Value::Nil;
+        for arg in args.into_iter().skip(1) {
+            result = eval(arg, local_env.clone(), false)?
+        }
+
+        Ok(result)
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let local_env = self.bind(&args, environment)?;
+        eval_body_tail(&args[1..], local_env)
+    }
+}
+
+impl LetMacro {
+    fn bind(&self, args: &[Value], environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Environment>>> {
         if args.is_empty() {
             return Err(arity_error_min(1, args.len()));
         }
@@ -204,22 +315,14 @@ impl Macro for LetMacro {
         }
 
         for pair in bind_form.value.chunks(2) {
-            let key = match &pair[0] {
-                Value::Symbol(sym) => Ok(sym),
-                _ => Err(Error::Type("let: first element of each pair must be a symbol".to_string())),
-            };
+            check_pattern(&pair[0], "let")?;
 
             let val = eval(pair[1].clone(), local_env.clone(), false)?;
 
-            local_env.borrow_mut().insert(key?, val)?;
-        }
-
-        let mut result = Value::Nil;
-        for arg in args.into_iter().skip(1) {
-            result = eval(arg, local_env.clone(), false)?
+            bind_pattern(&pair[0], val, &local_env)?;
         }
 
-        Ok(result)
+        Ok(local_env)
     }
 }
 
@@ -229,12 +332,92 @@ impl fmt::Display for LetMacro {
     }
 }
 
+// binding
+pub static SYMBOL_BINDING: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("binding"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Rebind dynamic variables while the body runs, including in the functions it calls: (binding [*precision* 3] ...).",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("binding"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingMacro;
+
+impl Macro for BindingMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let bind_form = match &args[0] {
+            Value::Vector(v) => v,
+            _ => return Err(Error::Syntax("binding: first argument must be a vector".to_string())),
+        };
+
+        if bind_form.value.len() % 2 != 0 {
+            return Err(Error::Syntax(
+                "binding: first argument must be a vector of even length".to_string(),
+            ));
+        }
+
+        // all values are evaluated before any variable is rebound
+        let mut bindings = vec![];
+        for pair in bind_form.value.chunks(2) {
+            let sym = match &pair[0] {
+                Value::Symbol(sym) => sym.clone(),
+                _ => return Err(Error::Syntax("binding: only symbols can be rebound".to_string())),
+            };
+            bindings.push((sym, eval(pair[1].clone(), environment.clone(), false)?));
+        }
+
+        let mut saved = vec![];
+        let mut result = Ok(Value::Nil);
+        for (sym, value) in bindings {
+            match environment.borrow_mut().rebind(&sym, value) {
+                Ok(old) => saved.push((sym, old)),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        if result.is_ok() {
+            for arg in args.into_iter().skip(1) {
+                result = eval(arg, environment.clone(), false);
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+
+        // restored even when the body fails or returns early
+        for (sym, old) in saved.into_iter().rev() {
+            environment.borrow_mut().rebind(&sym, old)?;
+        }
+
+        result
+    }
+}
+
+impl fmt::Display for BindingMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: binding>")
+    }
+}
+
 // quote(')
 pub static SYMBOL_QUOTE: Lazy<Symbol> = Lazy::new(|| Symbol {
     name: Cow::Borrowed("quote"),
     meta: Meta {
         doc: Cow::Borrowed("Quote a value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("quote"),
 });
@@ -264,6 +447,7 @@ pub static SYMBOL_SYNTAX_QUOTE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Syntax-quote a value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("syntax-quote"),
 });
@@ -285,7 +469,68 @@ impl Macro for SyntaxQuoteMacro {
             .borrow_mut()
             .insert(&SYMBOL_UNQUOTE_SPLICING, Value::Macro(Rc::new(UnquoteSplicingMacro)))?;
 
-        eval(args[0].clone(), local_env, true)
+        let mut gensyms = HashMap::new();
+        let form = auto_gensym(args[0].clone(), &mut gensyms, &environment)?;
+
+        eval(form, local_env, true)
+    }
+}
+
+// replace every 'name#' with the same generated symbol within one syntax-quote
+fn auto_gensym(value: Value, gensyms: &mut HashMap<String, Symbol>, environment: &Rc<RefCell<Environment>>) -> Result<Value> {
+    match value {
+        Value::Symbol(sym) if sym.name.len() > 1 && sym.name.ends_with('#') => {
+            let prefix = sym.name.trim_end_matches('#').to_string();
+            if let Some(generated) = gensyms.get(&prefix) {
+                return Ok(Value::Symbol(generated.clone()));
+            }
+            let name = format!("{}__{}__auto", prefix, environment.borrow_mut().next_gensym_id());
+            let generated = Symbol {
+                name: Cow::Owned(name.clone()),
+                meta: Default::default(),
+                hash: fxhash::hash(&name),
+            };
+            gensyms.insert(prefix, generated.clone());
+            Ok(Value::Symbol(generated))
+        }
+        Value::List(l) => {
+            // unquoted and nested syntax-quoted forms are left as they are
+            if let Some(Value::Symbol(sym)) = l.value.first() {
+                if *sym == *SYMBOL_UNQUOTE || *sym == *SYMBOL_UNQUOTE_SPLICING || *sym == *SYMBOL_SYNTAX_QUOTE {
+                    return Ok(Value::List(l));
+                }
+            }
+            let values = l
+                .value
+                .into_iter()
+                .map(|v| auto_gensym(v, gensyms, environment))
+                .collect::<Result<Vec<Value>>>()?;
+            Value::as_list(values)
+        }
+        Value::Vector(v) => {
+            let values = v
+                .value
+                .into_iter()
+                .map(|v| auto_gensym(v, gensyms, environment))
+                .collect::<Result<Vec<Value>>>()?;
+            Value::as_vector(values)
+        }
+        Value::Set(s) => {
+            let values = s
+                .value
+                .into_iter()
+                .map(|v| auto_gensym(v, gensyms, environment))
+                .collect::<Result<Vec<Value>>>()?;
+            Value::as_set(values)
+        }
+        Value::Map(m) => {
+            let mut values = vec![];
+            for (k, v) in m.value {
+                values.push((auto_gensym(k, gensyms, environment)?, auto_gensym(v, gensyms, environment)?));
+            }
+            Ok(Value::Map(Map::from(values)))
+        }
+        v => Ok(v),
     }
 }
 
@@ -301,6 +546,7 @@ pub static SYMBOL_UNQUOTE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Unquote a value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("unquote"),
 });
@@ -330,6 +576,7 @@ pub static SYMBOL_UNQUOTE_SPLICING: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Unquote-splicing a value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("unquote-splicing"),
 });
@@ -343,33 +590,18 @@ impl Macro for UnquoteSplicingMacro {
             return Err(arity_error(1, args.len()));
         }
 
-        let mut arg: Value = args[0].clone();
-        if let Value::Symbol(sym) = arg {
-            arg = environment.borrow().get(&sym)?;
-        }
+        let arg = eval(args[0].clone(), environment.clone(), false)?;
 
         let mut result: Vec<Value> = vec![];
 
         match arg {
-            Value::List(x) => {
-                for x in x.value.iter() {
-                    result.push(eval(x.clone(), environment.clone(), false)?);
-                }
-            }
-            Value::Vector(x) => {
-                for x in x.value.iter() {
-                    result.push(eval(x.clone(), environment.clone(), false)?);
-                }
-            }
-            Value::Set(x) => {
-                for x in x.value.iter() {
-                    result.push(eval(x.clone(), environment.clone(), false)?);
-                }
-            }
+            Value::Nil => {}
+            Value::List(x) => result.extend(x.value),
+            Value::Vector(x) => result.extend(x.value),
+            Value::Set(x) => result.extend(x.value),
             Value::Map(m) => {
-                for (k, v) in m.value.iter() {
-                    let x = Value::Vector(Vector::from([k.clone(), v.clone()].to_vec()));
-                    result.push(eval(x.clone(), environment.clone(), false)?);
+                for (k, v) in m.value.into_iter() {
+                    result.push(Value::Vector(Vector::from([k, v].to_vec())));
                 }
             }
             Value::String(s) => {
@@ -377,17 +609,9 @@ impl Macro for UnquoteSplicingMacro {
                     result.push(Value::String(c.to_string()));
                 }
             }
-            Value::Generator(g) => loop {
-                let next = g.borrow_mut().next();
-                match next {
-                    Some(v) => {
-                        result.push(v);
-                    }
-                    None => break,
-                }
-            },
+            Value::Generator(g) => result.extend(Cursor::new(Value::Generator(g))?.collect_values()?),
             _ => Err(Error::Type(
-                "unquote-splicing: argument must be a list, vector, set, map, string, or generator".to_string(),
+                "unquote-splicing: argument must be nil, list, vector, set, map, string, or generator".to_string(),
             ))?,
         }
 
@@ -409,6 +633,7 @@ pub static SYMBOL_DO: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Evaluate a series of expressions and return the last result."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("do"),
 });
@@ -425,6 +650,10 @@ impl Macro for DoMacro {
 
         Ok(result)
     }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        eval_body_tail(&args, environment)
+    }
 }
 
 impl fmt::Display for DoMacro {
@@ -441,6 +670,7 @@ pub static SYMBOL_IF: Lazy<Symbol> = Lazy::new(|| Symbol {
             "If the first argument is true, evaluate the second argument. Otherwise, evaluate the third argument.",
         ),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("if"),
 });
@@ -450,21 +680,30 @@ pub struct IfMacro;
 
 impl Macro for IfMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let branch = self.select_branch(args, environment.clone())?;
+        eval(branch, environment, false)
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let branch = self.select_branch(args, environment.clone())?;
+        eval_tail(branch, environment)
+    }
+}
+
+impl IfMacro {
+    fn select_branch(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
         if args.len() < 2 || args.len() > 3 {
             return Err(arity_error_range(2, 3, args.len()));
         }
 
         let condition = &args[0];
-        let truthy = eval(condition.clone(), environment.clone(), false)?;
+        let truthy = eval(condition.clone(), environment, false)?;
 
-        let result = if truthy.is_truthy() {
-            let true_branch = &args[1];
-            eval(true_branch.clone(), environment.clone(), false)?
+        if truthy.is_truthy() {
+            Ok(args[1].clone())
         } else {
-            let false_branch = if args.len() == 3 { &args[2] } else { &Value::Nil };
-            eval(false_branch.clone(), environment.clone(), false)?
-        };
-        Ok(result)
+            Ok(args.get(2).cloned().unwrap_or(Value::Nil))
+        }
     }
 }
 
@@ -480,6 +719,7 @@ pub static SYMBOL_WHEN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("When the first expression is true, evaluate the second expression."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("when"),
 });
@@ -507,6 +747,27 @@ impl Macro for WhenMacro {
             Ok(Value::Nil)
         }
     }
+
+    fn expand(&self, args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
+        Ok(Some(self.expand_form(args)?))
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        eval_tail(self.expand_form(args)?, environment)
+    }
+}
+
+impl WhenMacro {
+    fn expand_form(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let mut body = vec![Value::Symbol(SYMBOL_DO.clone())];
+        body.extend(args[1..].iter().cloned());
+
+        Value::as_list(vec![Value::Symbol(SYMBOL_IF.clone()), args[0].clone(), Value::as_list(body)?])
+    }
 }
 
 impl fmt::Display for WhenMacro {
@@ -521,6 +782,7 @@ pub static SYMBOL_BREAK: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Break out of a while/for loop."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("break"),
 });
@@ -554,6 +816,7 @@ pub static SYMBOL_CONTINUE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Continue a while/for loop."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("continue"),
 });
@@ -583,6 +846,7 @@ pub static SYMBOL_WHILE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("While the first expression is true, evaluate the second expression."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("while"),
 });
@@ -642,6 +906,7 @@ pub static SYMBOL_SWITCH: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Switch macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("switch"),
 });
@@ -651,16 +916,32 @@ pub struct SwitchMacro;
 
 impl Macro for SwitchMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        match self.select_expr(args, environment.clone())? {
+            Some(expr) => eval(expr, environment, false),
+            None => Ok(Value::Nil),
+        }
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        match self.select_expr(args, environment.clone())? {
+            Some(expr) => eval_tail(expr, environment),
+            None => Ok(Value::Nil),
+        }
+    }
+}
+
+impl SwitchMacro {
+    // expression of the first matching case, None when no case matches
+    fn select_expr(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
         if args.is_empty() {
             return Err(arity_error_min(1, args.len()));
         }
 
-        if args[1..].len() % 2 != 0 {
+        if !args[1..].len().is_multiple_of(2) {
             return Err(Error::Syntax("switch: case and expression must be in pairs".to_string()));
         }
 
         let val = eval(args[0].clone(), environment.clone(), false)?;
-        let mut result = Value::Nil;
 
         for chunk in args[1..].chunks(2) {
             let case = eval(chunk[0].clone(), environment.clone(), false)?;
@@ -668,23 +949,23 @@ impl Macro for SwitchMacro {
 
             match case {
                 Value::Vector(case) => {
-                    if case.value.iter().any(|v| *v == val) {
-                        result = eval(expr.clone(), environment.clone(), false)?;
-                        break;
-                    }
-                }
-                Value::Keyword(case) => {
-                    if case.name == ":default" {
-                        result = eval(expr.clone(), environment.clone(), false)?;
-                        break;
-                    } else {
-                        return Err(Error::Syntax("switch: case must be a vector or :default keyword".to_string()));
+                    if case.value.iter().any(|c| switch_case_matches(c, &val)) {
+                        return Ok(Some(expr.clone()));
                     }
                 }
+                Value::Keyword(case) if case.name == ":default" => return Ok(Some(expr.clone())),
                 _ => return Err(Error::Syntax("switch: case must be a vector or :default keyword".to_string())),
             }
         }
-        Ok(result)
+        Ok(None)
+    }
+}
+
+// equal values, or a payload variant constructor matching its instances
+fn switch_case_matches(case: &Value, value: &Value) -> bool {
+    match (case, value) {
+        (Value::Function(f), Value::Enum(e)) => e.is_constructed_by(f),
+        _ => case == value,
     }
 }
 
@@ -694,12 +975,88 @@ impl fmt::Display for SwitchMacro {
     }
 }
 
+// match
+pub static SYMBOL_MATCH: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("match"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Match a value: (match expr pattern [:when guard] body ...) with literal, symbol, _, [a & r], {:k p}, (pred? p).",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("match"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchMacro;
+
+impl Macro for MatchMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let (body, local_env) = self.select_clause(args, environment)?;
+        eval(body, local_env, false)
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let (body, local_env) = self.select_clause(args, environment)?;
+        eval_tail(body, local_env)
+    }
+}
+
+impl MatchMacro {
+    // body of the first matching clause and the environment of its bindings
+    fn select_clause(
+        &self,
+        args: Vec<Value>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(Value, Rc<RefCell<Environment>>)> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let value = eval(args[0].clone(), environment.clone(), false)?;
+
+        let mut rest = &args[1..];
+        while let Some((pattern, tail)) = rest.split_first() {
+            let (guard, tail) = match tail {
+                [Value::Keyword(k), guard, tail @ ..] if k.name == ":when" => (Some(guard), tail),
+                tail => (None, tail),
+            };
+            let (body, tail) = match tail.split_first() {
+                Some(split) => split,
+                None => return Err(Error::Syntax(format!("match: pattern {} has no body", pattern))),
+            };
+            rest = tail;
+
+            let local_env = Environment::new_local_environment(environment.clone());
+            if !match_pattern(pattern, &value, &local_env)? {
+                continue;
+            }
+            if let Some(guard) = guard {
+                if !eval(guard.clone(), local_env.clone(), false)?.is_truthy() {
+                    continue;
+                }
+            }
+            return Ok((body.clone(), local_env));
+        }
+
+        Err(Error::Value(format!("match: no clause matched {:?}", value)))
+    }
+}
+
+impl fmt::Display for MatchMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: match>")
+    }
+}
+
 // time
 pub static SYMBOL_TIME: Lazy<Symbol> = Lazy::new(|| Symbol {
     name: Cow::Borrowed("time"),
     meta: Meta {
         doc: Cow::Borrowed("Time the evaluation of an expression."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("time"),
 });
@@ -734,6 +1091,7 @@ pub static SYMBOL_DOC: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Get the documentation of a value."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("doc"),
 });
@@ -756,11 +1114,12 @@ impl Macro for DocMacro {
 
         let mut result = "------------------------------\n".to_string();
         result += format!("{}: {}\n", val.type_name(), key.name).as_str();
+        if !key.meta.arglists.is_empty() {
+            result += format!("({})\n", key.meta.arglists.join(" ")).as_str();
+        }
         result += format!("{}\n", key.meta.doc).as_str();
         result += "------------------------------";
 
-        // TODO: generate doc about arity
-
         println!("{}", result);
 
         Ok(Value::Nil)
@@ -779,6 +1138,7 @@ pub static SYMBOL_FN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Create a anonymous/lambda function."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("fn"),
 });
@@ -788,39 +1148,66 @@ pub struct FnMacro;
 
 impl Macro for FnMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
-        if args.len() < 2 {
+        if args.is_empty() || (args.len() < 2 && args[0].is_vector()) {
             return Err(arity_error_min(2, args.len()));
         }
 
-        let mut params = vec![];
-        let mut body = vec![];
-
-        for (i, arg) in args.into_iter().enumerate() {
-            if i == 0 {
-                let params_vec = match arg {
-                    Value::Vector(v) => v.value,
-                    _ => return Err(Error::Type("fn: first argument must be a vector".to_string())),
-                };
-
-                for param in params_vec {
-                    match param {
-                        Value::Symbol(sym) => params.push(sym),
-                        _ => return Err(Error::Type("fn: first argument must be a vector of symbols".to_string())),
-                    }
-                }
-            } else {
-                body.push(arg);
-            }
-        }
-
         Ok(Value::Function(Rc::new(Lambda {
-            args: params,
-            body,
+            arities: parse_arities(&args, "fn", &environment)?,
             environment: environment.clone(),
         })))
     }
 }
 
+// [params] body... or ([params] body...) ([params] body...) ...
+fn parse_arities(forms: &[Value], form: &str, environment: &Rc<RefCell<Environment>>) -> Result<Vec<Arity>> {
+    let resolve = |t: &Type| environment.borrow().resolve_type(t);
+    let parse_arity = |params: &Vector, body: &[Value]| {
+        let (params, types) = split_annotations(params, form)?;
+        let types = types.iter().map(resolve).collect::<Result<Vec<Type>>>()?;
+        // [params] => #ret body...
+        let (ret, body) = match body {
+            [Value::Symbol(arrow), Value::Type(ret), body @ ..] if arrow.name == "=>" => (resolve(ret)?, body),
+            [Value::Symbol(arrow), ..] if arrow.name == "=>" => {
+                return Err(Error::Syntax(format!("{}: => must be followed by a return type", form)))
+            }
+            // [params] #ret body... would silently drop the annotation
+            [Value::Type(ret), _, ..] => {
+                return Err(Error::Syntax(format!(
+                    "{}: the return type must follow =>: [...] => {}",
+                    form, ret
+                )))
+            }
+            body => (Type::Any, body),
+        };
+        Ok(Arity {
+            args: parse_params(&params, form)?,
+            types,
+            ret,
+            body: body.to_vec(),
+        })
+    };
+
+    match &forms[0] {
+        Value::Vector(params) => Ok(vec![parse_arity(params, &forms[1..])?]),
+        Value::List(_) => forms
+            .iter()
+            .map(|arity| {
+                let arity = match arity {
+                    Value::List(l) if !l.value.is_empty() => l,
+                    _ => return Err(Error::Syntax(format!("{}: each arity must be a list", form))),
+                };
+                let params = match &arity.value[0] {
+                    Value::Vector(v) => v,
+                    _ => return Err(Error::Syntax(format!("{}: arity must start with a vector", form))),
+                };
+                parse_arity(params, &arity.value[1..])
+            })
+            .collect(),
+        _ => Err(Error::Syntax(format!("{}: informal form", form))),
+    }
+}
+
 impl fmt::Display for FnMacro {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<builtin macro: fn>")
@@ -833,6 +1220,7 @@ pub static SYMBOL_DEFN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Bind a function to a symbol."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("defn"),
 });
@@ -851,48 +1239,26 @@ impl Macro for DefnMacro {
             _ => return Err(Error::Type("defn: first argument must be a symbol".to_string())),
         };
 
-        let params;
-        let bodies: &[Value];
-
-        if args.len() == 2 {
-            if let Value::Vector(_) = args[1] {
-                params = args[1].clone();
-                bodies = &[Value::Nil];
-            } else {
+        let mut rest = &args[1..];
+        if let Value::String(s) = &rest[0] {
+            if rest.len() < 2 {
                 return Err(Error::Syntax("defn: informal form".to_string()));
             }
-        } else {
-            match &args[1] {
-                Value::String(s) => {
-                    if let Value::Vector(_) = args[2] {
-                        symbol.meta.doc = Cow::Owned(s.clone());
-                        params = args[2].clone();
-                        bodies = &[Value::Nil];
-                    } else {
-                        return Err(Error::Syntax("defn: informal form".to_string()));
-                    }
-                }
-                Value::Vector(_) => {
-                    params = args[1].clone();
-                    bodies = &args[2..];
-                }
-                _ => return Err(Error::Syntax("defn: informal form".to_string())),
-            }
+            symbol.meta.doc = Cow::Owned(s.clone());
+            rest = &rest[1..];
         }
 
-        let mut symbols = vec![];
-        for p in params {
-            match p {
-                Value::Symbol(sym) => symbols.push(sym),
-                _ => return Err(Error::Syntax("defn: parameters must be symbols".to_string())),
-            }
-        }
+        let arities = parse_arities(rest, "defn", &environment)?;
+        symbol.meta.arglists = arities.iter().map(typed_arglist).collect();
 
-        let exec_bodies = bodies.to_vec();
+        // annotated methods make a generic function, and later methods extend it
+        let generic = bound_generic(&symbol, &environment);
+        if generic.is_some() || arities.iter().any(|a| a.is_annotated()) {
+            return define_generic(symbol, arities, generic, environment);
+        }
 
         let lambda = Lambda {
-            args: symbols,
-            body: exec_bodies,
+            arities,
             environment: environment.clone(),
         };
 
@@ -902,49 +1268,179 @@ impl Macro for DefnMacro {
     }
 }
 
+// the generic function currently bound to the symbol with its key
+fn bound_generic(symbol: &Symbol, environment: &Rc<RefCell<Environment>>) -> Option<(Symbol, Rc<Generic>)> {
+    let registered = environment.borrow().generics.borrow().get(symbol.name.as_ref()).cloned()?;
+    match environment.borrow().get_key_value(symbol) {
+        Ok((key, Value::Function(f))) if Rc::ptr_eq(&f, &(registered.clone() as Rc<dyn Function>)) => Some((key, registered)),
+        _ => None,
+    }
+}
+
+fn define_generic(
+    mut symbol: Symbol,
+    arities: Vec<Arity>,
+    bound: Option<(Symbol, Rc<Generic>)>,
+    environment: Rc<RefCell<Environment>>,
+) -> Result<Value> {
+    let generic = match bound {
+        Some((key, generic)) => {
+            let mut arglists = key.meta.arglists.clone();
+            arglists.retain(|a| !symbol.meta.arglists.contains(a));
+            arglists.append(&mut symbol.meta.arglists);
+            symbol.meta.arglists = arglists;
+            if symbol.meta.doc == Meta::default().doc {
+                symbol.meta.doc = key.meta.doc.clone();
+            }
+            generic
+        }
+        None => Rc::new(Generic {
+            name: symbol.name.to_string(),
+            methods: RefCell::new(vec![]),
+        }),
+    };
+
+    for arity in arities {
+        generic.add_method(Method {
+            function: Rc::new(Lambda {
+                arities: vec![arity.clone()],
+                environment: environment.clone(),
+            }),
+            arity,
+        });
+    }
+
+    environment
+        .borrow()
+        .generics
+        .borrow_mut()
+        .insert(generic.name.clone(), generic.clone());
+    environment.borrow_mut().insert(&symbol, Value::Function(generic.clone()))?;
+
+    // replace the key too, so that doc shows the arglists of all methods
+    let current = environment.borrow().current.clone();
+    current.borrow_mut().remove(&symbol);
+    current.borrow_mut().insert(symbol.clone(), Value::Function(generic));
+
+    Ok(Value::Symbol(symbol))
+}
+
 impl fmt::Display for DefnMacro {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<builtin macro: defn>")
     }
 }
 
-// thread-first(->)
-pub static SYMBOL_THREAD_FIRST: Lazy<Symbol> = Lazy::new(|| Symbol {
-    name: Cow::Borrowed("->"),
+// defgen
+pub static SYMBOL_DEFGEN: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("defgen"),
     meta: Meta {
-        doc: Cow::Borrowed("Thread-first macro."),
+        doc: Cow::Borrowed(
+            "Bind a generator function to a symbol: each call returns a generator whose body is suspended at each (yield x).",
+        ),
         mutable: false,
+        arglists: vec![],
     },
-    hash: fxhash::hash("->"),
+    hash: fxhash::hash("defgen"),
 });
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ThreadFirstMacro;
+pub struct DefgenMacro;
 
-impl Macro for ThreadFirstMacro {
+impl Macro for DefgenMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
-        if args.is_empty() {
-            return Err(arity_error_min(1, args.len()));
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
         }
 
-        let mut result = eval(args[0].clone(), environment.clone(), false)?;
+        let mut symbol = match args[0].clone() {
+            Value::Symbol(sym) => sym,
+            _ => return Err(Error::Type("defgen: first argument must be a symbol".to_string())),
+        };
 
-        for arg in args.into_iter().skip(1) {
-            match arg {
-                Value::List(mut list) => {
-                    list.value.insert(1, result.clone());
-                    result = eval(Value::List(list), environment.clone(), false)?;
-                }
-                Value::Symbol(sym) => {
-                    let new_list = Value::as_list(vec![Value::Symbol(sym), result.clone()])?;
-                    result = eval(new_list, environment.clone(), false)?;
-                }
-                _ => return Err(Error::Type("->: arguments must be lists, functions or macros".to_string())),
+        let mut rest = &args[1..];
+        if let Value::String(s) = &rest[0] {
+            if rest.len() < 2 {
+                return Err(Error::Syntax("defgen: informal form".to_string()));
             }
+            symbol.meta.doc = Cow::Owned(s.clone());
+            rest = &rest[1..];
         }
 
-        Ok(result)
-    }
+        let arities = parse_arities(rest, "defgen", &environment)?;
+        symbol.meta.arglists = arities.iter().map(typed_arglist).collect();
+
+        let function = GeneratorFunction {
+            name: symbol.name.to_string(),
+            function: Rc::new(Lambda {
+                arities,
+                environment: environment.clone(),
+            }),
+        };
+        environment.borrow_mut().insert(&symbol, Value::Function(Rc::new(function)))?;
+
+        Ok(Value::Symbol(symbol))
+    }
+}
+
+impl fmt::Display for DefgenMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: defgen>")
+    }
+}
+
+// thread-first(->)
+pub static SYMBOL_THREAD_FIRST: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("->"),
+    meta: Meta {
+        doc: Cow::Borrowed("Thread-first macro."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("->"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadFirstMacro;
+
+impl Macro for ThreadFirstMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let expanded = self.expand_form(args)?;
+        eval(expanded, environment, false)
+    }
+
+    fn expand(&self, args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
+        Ok(Some(self.expand_form(args)?))
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        eval_tail(self.expand_form(args)?, environment)
+    }
+}
+
+impl ThreadFirstMacro {
+    fn expand_form(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let mut result = args[0].clone();
+
+        for arg in args.into_iter().skip(1) {
+            match arg {
+                Value::List(mut list) if !list.value.is_empty() => {
+                    list.value.insert(1, result);
+                    result = Value::List(list);
+                }
+                Value::Symbol(sym) => {
+                    result = Value::as_list(vec![Value::Symbol(sym), result])?;
+                }
+                _ => return Err(Error::Type("->: arguments must be lists, functions or macros".to_string())),
+            }
+        }
+
+        Ok(result)
+    }
 }
 
 impl fmt::Display for ThreadFirstMacro {
@@ -959,6 +1455,7 @@ pub static SYMBOL_THREAD_LAST: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Thread-last macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("->>"),
 });
@@ -968,23 +1465,37 @@ pub struct ThreadLastMacro;
 
 impl Macro for ThreadLastMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let expanded = self.expand_form(args)?;
+        eval(expanded, environment, false)
+    }
+
+    fn expand(&self, args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
+        Ok(Some(self.expand_form(args)?))
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        eval_tail(self.expand_form(args)?, environment)
+    }
+}
+
+impl ThreadLastMacro {
+    fn expand_form(&self, args: Vec<Value>) -> Result<Value> {
         if args.is_empty() {
             return Err(arity_error_min(1, args.len()));
         }
 
-        let mut result = eval(args[0].clone(), environment.clone(), false)?;
+        let mut result = args[0].clone();
 
         for arg in args.into_iter().skip(1) {
             match arg {
-                Value::List(mut list) => {
+                Value::List(mut list) if !list.value.is_empty() => {
                     list.value.push(result);
-                    result = eval(Value::List(list), environment.clone(), false)?;
+                    result = Value::List(list);
                 }
                 Value::Symbol(sym) => {
-                    let new_list = Value::as_list(vec![Value::Symbol(sym), result.clone()])?;
-                    result = eval(new_list, environment.clone(), false)?;
+                    result = Value::as_list(vec![Value::Symbol(sym), result])?;
                 }
-                _ => return Err(Error::Type("->: arguments must be lists, functions or macros".to_string())),
+                _ => return Err(Error::Type("->>: arguments must be lists, functions or macros".to_string())),
             }
         }
 
@@ -1004,6 +1515,7 @@ pub static SYMBOL_COND: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Cond macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("cond"),
 });
@@ -1013,7 +1525,7 @@ pub struct CondMacro;
 
 impl Macro for CondMacro {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
-        if args.len() % 2 != 0 {
+        if !args.len().is_multiple_of(2) {
             return Err(Error::Syntax("cond: case and expression must be in pairs".to_string()));
         }
 
@@ -1032,6 +1544,34 @@ impl Macro for CondMacro {
         }
         Ok(result)
     }
+
+    // (cond c1 e1 c2 e2 ...) => (if c1 e1 (cond c2 e2 ...))
+    fn expand(&self, args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
+        if !args.len().is_multiple_of(2) {
+            return Err(Error::Syntax("cond: case and expression must be in pairs".to_string()));
+        }
+
+        if args.is_empty() {
+            return Ok(Some(Value::Nil));
+        }
+
+        let mut rest = vec![Value::Symbol(SYMBOL_COND.clone())];
+        rest.extend(args[2..].iter().cloned());
+
+        let mut result = vec![Value::Symbol(SYMBOL_IF.clone()), args[0].clone(), args[1].clone()];
+        if args.len() > 2 {
+            result.push(Value::as_list(rest)?);
+        }
+
+        Ok(Some(Value::as_list(result)?))
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        match self.expand(args, environment.clone())? {
+            Some(expanded) => eval_tail(expanded, environment),
+            None => Ok(Value::Nil),
+        }
+    }
 }
 
 impl fmt::Display for CondMacro {
@@ -1046,6 +1586,7 @@ pub static SYMBOL_AND: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("And macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("and"),
 });
@@ -1081,6 +1622,7 @@ pub static SYMBOL_OR: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Or macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("or"),
 });
@@ -1116,6 +1658,7 @@ pub static SYMBOL_FOR: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("For macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("for"),
 });
@@ -1146,36 +1689,30 @@ impl Macro for ForMacro {
             Err(Error::Type("for: first argument must be a vector of length 2".to_string()))?
         }
 
-        let param_symbol = match binding.value[0].clone() {
-            Value::Symbol(sym) => sym,
-            _ => Err(Error::Type("for: first element of binding must be a symbol".to_string()))?,
-        };
+        let pattern = binding.value[0].clone();
+        check_pattern(&pattern, "for")?;
 
         let param_body = binding.value[1].clone();
 
-        let mut iterator = match param_body {
+        let mut cursor = match param_body {
             Value::Symbol(_) | Value::List(_) | Value::Vector(_) | Value::Set(_) | Value::Map(_) => {
-                eval(param_body, local_env.clone(), false)?
+                Cursor::new(eval(param_body, local_env.clone(), false)?)?
             }
-            Value::Generator(g) => Value::Generator(g),
+            Value::Generator(g) => Cursor::new(Value::Generator(g))?,
             _ => Err(Error::Type(
                 "for: second element of binding must be a symbol, list, vector, set, or map".to_string(),
             ))?,
-        }
-        .into_iter();
-
-        // TODO: slow down
-        local_env.borrow_mut().insert(&param_symbol, Value::Nil)?;
+        };
 
         let mut result = Value::Nil;
         'looptop: loop {
-            let v = iterator.next();
-            if v.is_none() {
-                break;
-            }
+            let v = match cursor.next_value()? {
+                Some(v) => v,
+                None => break,
+            };
 
             // TODO: slow down
-            local_env.borrow_mut().set(&param_symbol, v.unwrap())?;
+            bind_pattern(&pattern, v, &local_env)?;
 
             for arg in args.iter().skip(1) {
                 let ret = eval(arg.clone(), local_env.clone(), false)?;
@@ -1208,6 +1745,7 @@ pub static SYMBOL_GENSYM: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Create a unique symbol."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("gensym"),
 });
@@ -1221,22 +1759,23 @@ impl Macro for GensymMacro {
             return Err(arity_error_range(0, 1, args.len()));
         }
 
-        let name = if args.is_empty() {
-            format!("gensym-{}", environment.borrow().gensym_id)
+        let prefix = if args.is_empty() {
+            "gensym".to_string()
         } else {
             match args[0] {
-                Value::String(ref s) => format!("{}-{}", s, environment.borrow().gensym_id),
+                Value::String(ref s) => s.clone(),
                 _ => return Err(type_error("string", args[0].type_name().as_str())),
             }
         };
 
-        environment.borrow_mut().gensym_id += 1;
+        let name = format!("{}-{}", prefix, environment.borrow_mut().next_gensym_id());
 
         Ok(Value::Symbol(Symbol {
             name: Cow::Owned(name.clone()),
             meta: Meta {
                 doc: Cow::Borrowed("Generated symbol by gensym."),
                 mutable: false,
+                arglists: vec![],
             },
             hash: fxhash::hash(&name),
         }))
@@ -1255,6 +1794,7 @@ pub static SYMBOL_RETURN: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Return a value from a function."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("return"),
 });
@@ -1282,12 +1822,133 @@ impl fmt::Display for ReturnMacro {
     }
 }
 
+// loop
+pub static SYMBOL_LOOP: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("loop"),
+    meta: Meta {
+        doc: Cow::Borrowed("Bind values like let and evaluate the body. recur rebinds them and runs the body again."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("loop"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopMacro;
+
+impl Macro for LoopMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        self.run(args, environment, false)
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        self.run(args, environment, true)
+    }
+}
+
+impl LoopMacro {
+    fn run(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>, tail: bool) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let bind_form = match &args[0] {
+            Value::Vector(v) => v,
+            _ => return Err(Error::Syntax("loop: first argument must be a vector".to_string())),
+        };
+
+        if !bind_form.value.len().is_multiple_of(2) {
+            return Err(Error::Syntax(
+                "loop: first argument must be a vector of even length".to_string(),
+            ));
+        }
+
+        let mut local_env = Environment::new_local_environment(environment.clone());
+        let mut patterns = vec![];
+        for pair in bind_form.value.chunks(2) {
+            check_pattern(&pair[0], "loop")?;
+            let val = eval(pair[1].clone(), local_env.clone(), false)?;
+            bind_pattern(&pair[0], val, &local_env)?;
+            patterns.push(&pair[0]);
+        }
+
+        loop {
+            // the body is always in tail position for recur, a tail call is finished here unless the loop is
+            let result = match eval_body_tail(&args[1..], local_env.clone()) {
+                Err(Error::TailCall(func, args, rets)) if !tail => finish_tail_call(func, args, rets),
+                result => result,
+            };
+
+            let values = match result {
+                Err(Error::Recur(values)) => values,
+                result => return result,
+            };
+
+            if values.len() != patterns.len() {
+                return Err(Error::Arity(format!(
+                    "recur: expected {} values for loop bindings, got {}",
+                    patterns.len(),
+                    values.len()
+                )));
+            }
+
+            local_env = Environment::new_local_environment(environment.clone());
+            for (pattern, val) in patterns.iter().zip(values) {
+                bind_pattern(pattern, val, &local_env)?;
+            }
+        }
+    }
+}
+
+impl fmt::Display for LoopMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: loop>")
+    }
+}
+
+// recur
+pub static SYMBOL_RECUR: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("recur"),
+    meta: Meta {
+        doc: Cow::Borrowed("Jump back to the innermost loop or fn with new bindings, without growing the stack."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("recur"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurMacro;
+
+impl Macro for RecurMacro {
+    // a recur outside tail position would throw away the work still pending around it
+    fn call(&self, _args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        Err(Error::Syntax("recur must be in tail position".to_string()))
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let values = args
+            .into_iter()
+            .map(|v| eval(v, environment.clone(), false))
+            .collect::<Result<Vec<Value>>>()?;
+
+        Err(Error::Recur(values))
+    }
+}
+
+impl fmt::Display for RecurMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: recur>")
+    }
+}
+
 // insert
 pub static SYMBOL_INSERTE: Lazy<Symbol> = Lazy::new(|| Symbol {
     name: Cow::Borrowed("insert!"),
     meta: Meta {
         doc: Cow::Borrowed("Insert a value into a collection"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("insert!"),
 });
@@ -1430,6 +2091,7 @@ pub static SYMBOL_REMOVEE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Remove a value from a collection"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("remove!"),
 });
@@ -1558,6 +2220,7 @@ pub static SYMBOL_REPLACEE: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Replace a value in a collection"),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("replace!"),
 });
@@ -1681,6 +2344,7 @@ pub static SYMBOL_MACRO: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed("Create a macro."),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("macro"),
 });
@@ -1689,11 +2353,38 @@ pub static SYMBOL_MACRO: Lazy<Symbol> = Lazy::new(|| Symbol {
 pub struct MacroMacro;
 
 impl Macro for MacroMacro {
-    fn call(&self, args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Value> {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
         if args.len() < 2 {
             return Err(arity_error_min(2, args.len()));
         }
-        Ok(Value::Nil)
+
+        let mut symbol = match args[0].clone() {
+            Value::Symbol(sym) => sym,
+            _ => return Err(Error::Type("macro: first argument must be a symbol".to_string())),
+        };
+
+        let mut rest = &args[1..];
+        if let Value::String(s) = &rest[0] {
+            if rest.len() < 2 {
+                return Err(Error::Syntax("macro: informal form".to_string()));
+            }
+            symbol.meta.doc = Cow::Owned(s.clone());
+            rest = &rest[1..];
+        }
+
+        // (macro name [params] body...) or (macro name ([params] body...) ...)
+        let arities = parse_arities(rest, "macro", &environment)?;
+        symbol.meta.arglists = arities.iter().map(|a| arglist(&a.args)).collect();
+
+        let user_macro = UserMacro {
+            name: symbol.clone(),
+            arities,
+            environment: environment.clone(),
+        };
+
+        environment.borrow_mut().insert(&symbol, Value::Macro(Rc::new(user_macro)))?;
+
+        Ok(Value::Symbol(symbol))
     }
 }
 
@@ -1703,11 +2394,1507 @@ impl fmt::Display for MacroMacro {
     }
 }
 
+// macroexpand helpers
+pub fn macroexpand_1(form: Value, environment: Rc<RefCell<Environment>>) -> Result<(Value, bool)> {
+    let list = match &form {
+        Value::List(l) if !l.value.is_empty() => l,
+        _ => return Ok((form, false)),
+    };
+
+    let mac = match &list.value[0] {
+        Value::Symbol(sym) => match environment.borrow().get(sym) {
+            Ok(Value::Macro(mac)) => mac,
+            _ => return Ok((form, false)),
+        },
+        _ => return Ok((form, false)),
+    };
+
+    match mac.expand(list.value[1..].to_vec(), environment.clone())? {
+        Some(expanded) => Ok((expanded, true)),
+        None => Ok((form, false)),
+    }
+}
+
+pub fn macroexpand(form: Value, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+    let mut result = form;
+    loop {
+        let (expanded, changed) = macroexpand_1(result, environment.clone())?;
+        result = expanded;
+        if !changed {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+pub fn macroexpand_all(form: Value, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+    let form = macroexpand(form, environment.clone())?;
+
+    let expand_all = |values: Vec<Value>| -> Result<Vec<Value>> {
+        values
+            .into_iter()
+            .map(|v| macroexpand_all(v, environment.clone()))
+            .collect::<Result<Vec<Value>>>()
+    };
+
+    match form {
+        Value::List(l) => {
+            // quoted forms are data
+            if let Some(Value::Symbol(sym)) = l.value.first() {
+                if *sym == *SYMBOL_QUOTE || *sym == *SYMBOL_SYNTAX_QUOTE {
+                    return Ok(Value::List(l));
+                }
+            }
+            Value::as_list(expand_all(l.value)?)
+        }
+        Value::Vector(v) => Value::as_vector(expand_all(v.value)?),
+        Value::Set(s) => Value::as_set(expand_all(s.value.into_iter().collect())?),
+        Value::Map(m) => {
+            let mut result = vec![];
+            for (k, v) in m.value {
+                result.push((k, macroexpand_all(v, environment.clone())?));
+            }
+            Value::as_map(result)
+        }
+        v => Ok(v),
+    }
+}
+
+// macroexpand-1
+pub static SYMBOL_MACROEXPAND_1: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("macroexpand-1"),
+    meta: Meta {
+        doc: Cow::Borrowed("Expand a quoted macro form one step without evaluating it."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("macroexpand-1"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Macroexpand1Macro;
+
+impl Macro for Macroexpand1Macro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let form = eval(args[0].clone(), environment.clone(), false)?;
+        Ok(macroexpand_1(form, environment)?.0)
+    }
+}
+
+impl fmt::Display for Macroexpand1Macro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: macroexpand-1>")
+    }
+}
+
+// macroexpand
+pub static SYMBOL_MACROEXPAND: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("macroexpand"),
+    meta: Meta {
+        doc: Cow::Borrowed("Expand a quoted macro form repeatedly until the head is no longer a macro."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("macroexpand"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroexpandMacro;
+
+impl Macro for MacroexpandMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let form = eval(args[0].clone(), environment.clone(), false)?;
+        macroexpand(form, environment)
+    }
+}
+
+impl fmt::Display for MacroexpandMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: macroexpand>")
+    }
+}
+
+// macroexpand-all
+pub static SYMBOL_MACROEXPAND_ALL: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("macroexpand-all"),
+    meta: Meta {
+        doc: Cow::Borrowed("Expand a quoted form and all of its subforms recursively."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("macroexpand-all"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroexpandAllMacro;
+
+impl Macro for MacroexpandAllMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let form = eval(args[0].clone(), environment.clone(), false)?;
+        macroexpand_all(form, environment)
+    }
+}
+
+impl fmt::Display for MacroexpandAllMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: macroexpand-all>")
+    }
+}
+
+// try
+pub static SYMBOL_TRY: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("try"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Evaluate expressions and handle exceptions with (catch Type e ...) clauses. (finally ...) is always evaluated.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("try"),
+});
+
+pub static SYMBOL_CATCH: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("catch"),
+    meta: Meta {
+        doc: Cow::Borrowed("Catch clause of try."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("catch"),
+});
+
+pub static SYMBOL_FINALLY: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("finally"),
+    meta: Meta {
+        doc: Cow::Borrowed("Finally clause of try."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("finally"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryMacro;
+
+struct CatchClause {
+    types: Vec<Symbol>,
+    binding: Symbol,
+    body: Vec<Value>,
+}
+
+impl CatchClause {
+    fn parse(form: &[Value]) -> Result<CatchClause> {
+        if form.len() < 2 {
+            return Err(Error::Syntax("catch: expected (catch Type e body...)".to_string()));
+        }
+
+        let types = match &form[0] {
+            Value::Symbol(sym) => vec![sym.clone()],
+            Value::Vector(v) => v
+                .value
+                .iter()
+                .map(|t| match t {
+                    Value::Symbol(sym) => Ok(sym.clone()),
+                    _ => Err(Error::Syntax("catch: exception types must be symbols".to_string())),
+                })
+                .collect::<Result<Vec<Symbol>>>()?,
+            _ => {
+                return Err(Error::Syntax(
+                    "catch: exception type must be a symbol or vector of symbols".to_string(),
+                ))
+            }
+        };
+
+        let binding = match &form[1] {
+            Value::Symbol(sym) => sym.clone(),
+            _ => return Err(Error::Syntax("catch: binding must be a symbol".to_string())),
+        };
+
+        Ok(CatchClause {
+            types,
+            binding,
+            body: form[2..].to_vec(),
+        })
+    }
+
+    fn matches(&self, exception: &Value) -> bool {
+        condition_matches(&self.types, exception)
+    }
+}
+
+impl Macro for TryMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let mut body = vec![];
+        let mut catches = vec![];
+        let mut finally: Option<Vec<Value>> = None;
+
+        for arg in args {
+            let clause = match &arg {
+                Value::List(l) => match l.value.first() {
+                    Some(Value::Symbol(sym)) if *sym == *SYMBOL_CATCH || *sym == *SYMBOL_FINALLY => {
+                        Some((sym.clone(), l.value[1..].to_vec()))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            match clause {
+                Some((sym, rest)) => {
+                    if finally.is_some() {
+                        return Err(Error::Syntax("try: finally must be the last clause".to_string()));
+                    }
+                    if sym == *SYMBOL_CATCH {
+                        catches.push(CatchClause::parse(&rest)?);
+                    } else {
+                        finally = Some(rest);
+                    }
+                }
+                None => {
+                    if !catches.is_empty() || finally.is_some() {
+                        return Err(Error::Syntax("try: body must come before catch/finally".to_string()));
+                    }
+                    body.push(arg);
+                }
+            }
+        }
+
+        let mut result = Ok(Value::Nil);
+        for form in body {
+            result = eval(form, environment.clone(), false);
+            if matches!(result, Err(_) | Ok(Value::ControlFlow(_))) {
+                break;
+            }
+        }
+
+        if let Err(err) = result {
+            result = match err.to_exception() {
+                Some(exception) => match catches.iter().find(|c| c.matches(&exception)) {
+                    Some(clause) => {
+                        let local_env = Environment::new_local_environment(environment.clone());
+                        local_env.borrow_mut().insert(&clause.binding, exception)?;
+                        let mut ret = Ok(Value::Nil);
+                        for form in clause.body.iter() {
+                            ret = eval(form.clone(), local_env.clone(), false);
+                            if ret.is_err() {
+                                break;
+                            }
+                        }
+                        ret
+                    }
+                    None => Err(err),
+                },
+                None => Err(err),
+            };
+        }
+
+        if let Some(forms) = finally {
+            for form in forms {
+                eval(form, environment.clone(), false)?;
+            }
+        }
+
+        result
+    }
+}
+
+impl fmt::Display for TryMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: try>")
+    }
+}
+
+// throw
+pub static SYMBOL_THROW: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("throw"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Throw an exception map, or a string as the message of an Exception. Handlers bound by handler-bind run first.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("throw"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrowMacro;
+
+impl Macro for ThrowMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let condition = to_condition(eval(args[0].clone(), environment.clone(), false)?)?;
+        signal(&condition, environment)?;
+
+        Err(Error::Exception(condition))
+    }
+}
+
+impl fmt::Display for ThrowMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: throw>")
+    }
+}
+
+// signal
+pub static SYMBOL_SIGNAL: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("signal"),
+    meta: Meta {
+        doc: Cow::Borrowed("Signal a condition to the handlers bound by handler-bind. Returns nil if all handlers decline."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("signal"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalMacro;
+
+impl Macro for SignalMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let condition = to_condition(eval(args[0].clone(), environment.clone(), false)?)?;
+        signal(&condition, environment)?;
+
+        Ok(Value::Nil)
+    }
+}
+
+impl fmt::Display for SignalMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: signal>")
+    }
+}
+
+// handler-bind
+pub static SYMBOL_HANDLER_BIND: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("handler-bind"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Bind condition handlers [Type handler ...] during the body. Handlers run without unwinding the stack.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("handler-bind"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerBindMacro;
+
+impl Macro for HandlerBindMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let bind_form = match &args[0] {
+            Value::Vector(v) => v,
+            _ => return Err(Error::Syntax("handler-bind: first argument must be a vector".to_string())),
+        };
+
+        if !bind_form.value.len().is_multiple_of(2) {
+            return Err(Error::Syntax(
+                "handler-bind: first argument must be a vector of even length".to_string(),
+            ));
+        }
+
+        let mut handlers = vec![];
+        for pair in bind_form.value.chunks(2) {
+            let types = match &pair[0] {
+                Value::Symbol(sym) => vec![sym.clone()],
+                Value::Vector(v) => v
+                    .value
+                    .iter()
+                    .map(|t| match t {
+                        Value::Symbol(sym) => Ok(sym.clone()),
+                        _ => Err(Error::Syntax("handler-bind: condition types must be symbols".to_string())),
+                    })
+                    .collect::<Result<Vec<Symbol>>>()?,
+                _ => {
+                    return Err(Error::Syntax(
+                        "handler-bind: condition type must be a symbol or vector".to_string(),
+                    ))
+                }
+            };
+            let function = eval(pair[1].clone(), environment.clone(), false)?;
+            if !matches!(function, Value::Function(_)) {
+                return Err(type_error("function", function.type_name().as_str()));
+            }
+            handlers.push(Handler { types, function });
+        }
+
+        // the first handler is the innermost one
+        let stack = environment.borrow().handlers.clone();
+        let depth = stack.borrow().len();
+        stack.borrow_mut().extend(handlers.into_iter().rev());
+
+        let mut result = Ok(Value::Nil);
+        for form in args.into_iter().skip(1) {
+            result = eval(form, environment.clone(), false);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        stack.borrow_mut().truncate(depth);
+        result
+    }
+}
+
+impl fmt::Display for HandlerBindMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: handler-bind>")
+    }
+}
+
+// restart-case
+pub static SYMBOL_RESTART_CASE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("restart-case"),
+    meta: Meta {
+        doc: Cow::Borrowed("Evaluate an expression with restarts (:name [args] body...) which handlers can invoke."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("restart-case"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartCaseMacro;
+
+impl Macro for RestartCaseMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let mut clauses = vec![];
+        for clause in args[1..].iter() {
+            let form = match clause {
+                Value::List(l) if l.value.len() >= 2 => &l.value,
+                _ => {
+                    return Err(Error::Syntax(
+                        "restart-case: restart must be (:name [args] body...)".to_string(),
+                    ))
+                }
+            };
+            let name = match &form[0] {
+                Value::Keyword(k) => k.clone(),
+                _ => return Err(Error::Syntax("restart-case: restart name must be a keyword".to_string())),
+            };
+            let params = match &form[1] {
+                Value::Vector(v) => parse_params(v, "restart-case")?,
+                _ => return Err(Error::Syntax("restart-case: restart parameters must be a vector".to_string())),
+            };
+            let restart = Restart {
+                name,
+                id: next_restart_id(),
+            };
+            clauses.push((restart, params, form[2..].to_vec()));
+        }
+
+        let stack = environment.borrow().restarts.clone();
+        let depth = stack.borrow().len();
+        stack.borrow_mut().extend(clauses.iter().rev().map(|(r, _, _)| r.clone()));
+
+        let result = eval(args[0].clone(), environment.clone(), false);
+
+        stack.borrow_mut().truncate(depth);
+
+        match result {
+            Err(Error::InvokeRestart(id, restart_args)) => {
+                let (_, params, body) = match clauses.iter().find(|(r, _, _)| r.id == id) {
+                    Some(clause) => clause,
+                    None => return Err(Error::InvokeRestart(id, restart_args)),
+                };
+                let local_env = Environment::new_local_environment(environment.clone());
+                bind_arguments(params, restart_args, &local_env)?;
+                let mut ret = Value::Nil;
+                for form in body {
+                    ret = eval(form.clone(), local_env.clone(), false)?;
+                }
+                Ok(ret)
+            }
+            result => result,
+        }
+    }
+}
+
+impl fmt::Display for RestartCaseMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: restart-case>")
+    }
+}
+
+// invoke-restart
+pub static SYMBOL_INVOKE_RESTART: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("invoke-restart"),
+    meta: Meta {
+        doc: Cow::Borrowed("Transfer control to the innermost active restart with the given name."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("invoke-restart"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvokeRestartMacro;
+
+impl Macro for InvokeRestartMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let mut args = args
+            .into_iter()
+            .map(|v| eval(v, environment.clone(), false))
+            .collect::<Result<Vec<Value>>>()?;
+
+        let name = match args.remove(0) {
+            Value::Keyword(k) => k,
+            v => return Err(type_error("keyword", v.type_name().as_str())),
+        };
+
+        let stack = environment.borrow().restarts.clone();
+        let restart = stack.borrow().iter().rev().find(|r| r.name == name).cloned();
+        match restart {
+            Some(restart) => Err(Error::InvokeRestart(restart.id, args)),
+            None => Err(Error::Value(format!("invoke-restart: no active restart named {}", name))),
+        }
+    }
+}
+
+impl fmt::Display for InvokeRestartMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: invoke-restart>")
+    }
+}
+
+// compute-restarts
+pub static SYMBOL_COMPUTE_RESTARTS: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("compute-restarts"),
+    meta: Meta {
+        doc: Cow::Borrowed("Get the names of the active restarts, innermost first."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("compute-restarts"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputeRestartsMacro;
+
+impl Macro for ComputeRestartsMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if !args.is_empty() {
+            return Err(arity_error(0, args.len()));
+        }
+
+        let stack = environment.borrow().restarts.clone();
+        let names = stack
+            .borrow()
+            .iter()
+            .rev()
+            .map(|r| Value::Keyword(r.name.clone()))
+            .collect::<Vec<Value>>();
+        Value::as_vector(names)
+    }
+}
+
+impl fmt::Display for ComputeRestartsMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: compute-restarts>")
+    }
+}
+
+// struct
+pub static SYMBOL_STRUCT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("struct"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Define a struct: (struct Name => Parent \"doc\" [fields] methods ...). A defn named Name is the constructor.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("struct"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructMacro;
+
+impl Macro for StructMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
+        }
+
+        let mut symbol = match args[0].clone() {
+            Value::Symbol(sym) => sym,
+            _ => return Err(Error::Type("struct: first argument must be a symbol".to_string())),
+        };
+
+        let mut rest = &args[1..];
+        let mut parent = None;
+        if let [Value::Symbol(arrow), Value::Symbol(parent_name), ..] = rest {
+            if arrow.name == "=>" {
+                parent = Some(lookup_struct(parent_name, &environment)?);
+                rest = &rest[2..];
+            }
+        }
+
+        if let Some(Value::String(s)) = rest.first() {
+            symbol.meta.doc = Cow::Owned(s.clone());
+            rest = &rest[1..];
+        }
+
+        let own_fields = match rest.first() {
+            Some(Value::Vector(v)) => v,
+            _ => return Err(Error::Syntax("struct: fields must be a vector".to_string())),
+        };
+
+        let mut fields = parent.as_ref().map(|p| p.fields.clone()).unwrap_or_default();
+        for field in own_fields.value.iter() {
+            let key = match field {
+                Value::Symbol(sym) => Keyword {
+                    name: format!(":{}", sym.name),
+                },
+                _ => return Err(Error::Syntax("struct: field names must be symbols".to_string())),
+            };
+            if fields.contains(&key) {
+                return Err(Error::Syntax(format!("struct: duplicate field {}", key)));
+            }
+            fields.push(key);
+        }
+
+        // instances keep their type, a redefinition would leave them behind the name
+        if environment.borrow().structs.contains_key(symbol.name.as_ref()) {
+            return Err(Error::Syntax(format!("struct: {} is already defined", symbol.name)));
+        }
+
+        let struct_type = Rc::new(StructType {
+            name: symbol.name.to_string(),
+            fields,
+            parent,
+            methods: RefCell::new(HashMap::new()),
+            constructor: RefCell::new(None),
+        });
+
+        // registered first, so that the methods can be annotated with the struct type
+        environment
+            .borrow_mut()
+            .structs
+            .insert(struct_type.name.clone(), struct_type.clone());
+        if let Err(err) = define_methods(std::slice::from_ref(&struct_type), &rest[1..], &environment, "struct") {
+            environment.borrow_mut().structs.remove(&struct_type.name);
+            return Err(err);
+        }
+        environment
+            .borrow_mut()
+            .insert(&symbol, Value::Function(Rc::new(StructConstructor { struct_type })))?;
+
+        Ok(Value::Symbol(symbol))
+    }
+}
+
+fn lookup_struct(symbol: &Symbol, environment: &Rc<RefCell<Environment>>) -> Result<Rc<StructType>> {
+    match environment.borrow().get_struct(symbol.name.as_ref()) {
+        Some(struct_type) => Ok(struct_type),
+        None => Err(Error::Type(format!("{} is not a struct", symbol))),
+    }
+}
+
+// evaluate defn forms and attach them to the struct types (a defn named as the struct is its constructor)
+fn define_methods(
+    struct_types: &[Rc<StructType>],
+    forms: &[Value],
+    environment: &Rc<RefCell<Environment>>,
+    form_name: &str,
+) -> Result<()> {
+    let local_env = Environment::new_local_environment(environment.clone());
+
+    for form in forms {
+        match form {
+            Value::List(l) if matches!(l.value.first(), Some(Value::Symbol(s)) if *s == *SYMBOL_DEFN) => {}
+            _ => return Err(Error::Syntax(format!("{}: methods must be defn forms", form_name))),
+        }
+
+        let name = match eval(form.clone(), local_env.clone(), false)? {
+            Value::Symbol(sym) => sym,
+            v => return Err(type_error("symbol", v.type_name().as_str())),
+        };
+        let method = local_env.borrow().get(&name)?;
+
+        for struct_type in struct_types {
+            if name.name == struct_type.name {
+                *struct_type.constructor.borrow_mut() = Some(method.clone());
+            } else {
+                struct_type.methods.borrow_mut().insert(name.name.to_string(), method.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for StructMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: struct>")
+    }
+}
+
+// method
+pub static SYMBOL_METHOD: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("method"),
+    meta: Meta {
+        doc: Cow::Borrowed("Define methods of structs outside of them: (method [Name ...] (defn method [self ...] ...))."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("method"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodMacro;
+
+impl Macro for MethodMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let struct_types = match &args[0] {
+            Value::Vector(v) => v
+                .value
+                .iter()
+                .map(|name| match name {
+                    Value::Symbol(sym) => lookup_struct(sym, &environment),
+                    _ => Err(Error::Syntax("method: struct names must be symbols".to_string())),
+                })
+                .collect::<Result<Vec<Rc<StructType>>>>()?,
+            _ => return Err(Error::Syntax("method: first argument must be a vector".to_string())),
+        };
+
+        define_methods(&struct_types, &args[1..], &environment, "method")?;
+
+        Ok(Value::Nil)
+    }
+}
+
+impl fmt::Display for MethodMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: method>")
+    }
+}
+
+// ancestor
+pub static SYMBOL_ANCESTOR: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("ancestor"),
+    meta: Meta {
+        doc: Cow::Borrowed("Get the ancestors of a struct or a struct instance, nearest first."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("ancestor"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AncestorMacro;
+
+impl Macro for AncestorMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let struct_type = match &args[0] {
+            Value::Symbol(sym) if environment.borrow().get_struct(sym.name.as_ref()).is_some() => {
+                lookup_struct(sym, &environment)?
+            }
+            arg => match eval(arg.clone(), environment.clone(), false)? {
+                Value::Struct(s) => s.borrow().struct_type.clone(),
+                v => return Err(type_error("struct", v.type_name().as_str())),
+            },
+        };
+
+        let ancestors = struct_type
+            .ancestors()
+            .iter()
+            .map(|t| Value::Symbol(runtime_symbol(&t.name)))
+            .collect::<Vec<Value>>();
+
+        Value::as_vector(ancestors)
+    }
+}
+
+impl fmt::Display for AncestorMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: ancestor>")
+    }
+}
+
+// . (member access)
+pub static SYMBOL_DOT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("."),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Access a member: (. obj :member) is obj.member, (.member obj args ...) reads a field or calls a method.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("."),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotMacro;
+
+impl Macro for DotMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
+        }
+
+        // (. obj :member args ...) or the call form (. obj (:member args ...)) read from (.member obj args ...)
+        let (key, arg_forms, call) = match &args[1] {
+            Value::Keyword(k) => (k.clone(), &args[2..], args.len() > 2),
+            Value::List(l) => match l.value.split_first() {
+                Some((Value::Keyword(k), rest)) if args.len() == 2 => (k.clone(), rest, true),
+                _ => return Err(Error::Syntax(".: member must be a keyword".to_string())),
+            },
+            _ => return Err(Error::Syntax(".: member must be a keyword".to_string())),
+        };
+
+        // fields of maps, structs and enum payloads, methods bound to struct instances
+        let target = eval(args[0].clone(), environment.clone(), false)?;
+        let is_method = matches!(&target, Value::Struct(s) if s.borrow().fields.get(&Value::Keyword(key.clone())).is_none());
+        let member = key.call(vec![target])?;
+        if !call || (arg_forms.is_empty() && !is_method) {
+            return Ok(member);
+        }
+
+        let rest = arg_forms
+            .iter()
+            .map(|arg| eval(arg.clone(), environment.clone(), false))
+            .collect::<Result<Vec<Value>>>()?;
+        match member {
+            Value::Function(f) => f.call(rest),
+            v => Err(Error::Type(format!(
+                "{} of {} is not callable: {}",
+                key,
+                args[0],
+                v.type_name()
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for DotMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: .>")
+    }
+}
+
+// enum
+pub static SYMBOL_ENUM: Lazy<Symbol> = Lazy::new(|| {
+    Symbol {
+    name: Cow::Borrowed("enum"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Define an enum: (enum Name \"doc\" [Unit (Variant field ...)]). Each variant is bound with a Variant? (also Name.Variant?) predicate.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("enum"),
+}
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumMacro;
+
+impl Macro for EnumMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(arity_error_range(2, 3, args.len()));
+        }
+
+        let mut symbol = match args[0].clone() {
+            Value::Symbol(sym) => sym,
+            _ => return Err(Error::Type("enum: first argument must be a symbol".to_string())),
+        };
+
+        if args.len() == 3 {
+            match &args[1] {
+                Value::String(s) => symbol.meta.doc = Cow::Owned(s.clone()),
+                _ => return Err(Error::Type("enum: docstring must be a string".to_string())),
+            }
+        }
+
+        let specs = match args.last() {
+            Some(Value::Vector(v)) => &v.value,
+            _ => return Err(Error::Syntax("enum: variants must be a vector".to_string())),
+        };
+
+        let mut variants: Vec<Variant> = vec![];
+        for spec in specs {
+            let (name, fields) = match spec {
+                Value::Symbol(sym) => (sym.name.to_string(), vec![]),
+                Value::List(l) => match l.value.split_first() {
+                    Some((Value::Symbol(sym), fields)) if !fields.is_empty() => {
+                        let fields = fields
+                            .iter()
+                            .map(|f| match f {
+                                Value::Symbol(f) => Ok(Keyword {
+                                    name: format!(":{}", f.name),
+                                }),
+                                _ => Err(Error::Syntax("enum: variant fields must be symbols".to_string())),
+                            })
+                            .collect::<Result<Vec<Keyword>>>()?;
+                        (sym.name.to_string(), fields)
+                    }
+                    _ => return Err(Error::Syntax(format!("enum: invalid variant {}", spec))),
+                },
+                _ => return Err(Error::Syntax(format!("enum: invalid variant {}", spec))),
+            };
+            if variants.iter().any(|v| v.name == name) {
+                return Err(Error::Syntax(format!("enum: duplicate variant {}", name)));
+            }
+            variants.push(Variant { name, fields });
+        }
+
+        // bare variant names must not take over other definitions, only a redefinition of this enum
+        let previous = environment.borrow().current.borrow().get(&symbol).cloned();
+        for variant in &variants {
+            let key = Value::Keyword(Keyword {
+                name: format!(":{}", variant.name),
+            });
+            if matches!(&previous, Some(Value::Map(m)) if m.get(&key).is_some()) {
+                continue;
+            }
+            for name in [variant.name.clone(), format!("{}?", variant.name)] {
+                if environment.borrow().current.borrow().contains_key(&runtime_symbol(&name)) {
+                    return Err(Error::Syntax(format!(
+                        "enum: {} is already defined, cannot define {}.{}",
+                        name, symbol.name, variant.name
+                    )));
+                }
+            }
+        }
+
+        let enum_type = EnumType::new(symbol.name.to_string(), variants);
+
+        // Name => {:Variant value, :Variant? predicate}, Variant => value or constructor, Variant? => predicate
+        let mut members = vec![];
+        for (i, variant) in enum_type.variants.iter().enumerate() {
+            let value = enum_type.variant_value(i);
+            let predicate = Value::Function(Rc::new(VariantPredicate {
+                enum_type: enum_type.clone(),
+                variant: i,
+            }));
+            let mut env = environment.borrow_mut();
+            env.insert(&runtime_symbol(&variant.name), value.clone())?;
+            env.insert(&runtime_symbol(&format!("{}?", variant.name)), predicate.clone())?;
+            members.push((
+                Value::Keyword(Keyword {
+                    name: format!(":{}", variant.name),
+                }),
+                value,
+            ));
+            members.push((
+                Value::Keyword(Keyword {
+                    name: format!(":{}?", variant.name),
+                }),
+                predicate,
+            ));
+        }
+
+        environment.borrow_mut().insert(&symbol, Value::as_map(members)?)?;
+        environment.borrow_mut().enums.insert(enum_type.name.clone(), enum_type);
+
+        Ok(Value::Symbol(symbol))
+    }
+}
+
+pub fn runtime_symbol(name: &str) -> Symbol {
+    Symbol {
+        name: Cow::Owned(name.to_string()),
+        meta: Meta::default(),
+        hash: fxhash::hash(name),
+    }
+}
+
+impl fmt::Display for EnumMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: enum>")
+    }
+}
+
+// defprotocol
+pub static SYMBOL_DEFPROTOCOL: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("defprotocol"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Define a protocol: (defprotocol Name \"doc\" (method [self ...] \"doc\") ...), dispatched on the first argument.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("defprotocol"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefprotocolMacro;
+
+impl Macro for DefprotocolMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
+        }
+
+        let mut symbol = match args[0].clone() {
+            Value::Symbol(sym) => sym,
+            _ => return Err(Error::Type("defprotocol: first argument must be a symbol".to_string())),
+        };
+
+        let mut rest = &args[1..];
+        if let Some(Value::String(s)) = rest.first() {
+            symbol.meta.doc = Cow::Owned(s.clone());
+            rest = &rest[1..];
+        }
+
+        // (method [self ...] ... "doc")
+        let mut signatures: Vec<Symbol> = vec![];
+        for signature in rest {
+            let forms = match signature {
+                Value::List(l) => &l.value,
+                _ => return Err(Error::Syntax("defprotocol: method signature must be a list".to_string())),
+            };
+            let mut method = match forms.first() {
+                Some(Value::Symbol(sym)) => sym.clone(),
+                _ => return Err(Error::Syntax("defprotocol: method name must be a symbol".to_string())),
+            };
+            for form in forms[1..].iter() {
+                match form {
+                    Value::Vector(params) if !params.value.is_empty() => {
+                        method.meta.arglists.push(arglist(&parse_params(params, "defprotocol")?))
+                    }
+                    Value::String(s) => method.meta.doc = Cow::Owned(s.clone()),
+                    _ => {
+                        return Err(Error::Syntax(format!(
+                            "defprotocol: {} must have parameter vectors with self",
+                            method
+                        )))
+                    }
+                }
+            }
+            if method.meta.arglists.is_empty() || signatures.iter().any(|m| m.name == method.name) {
+                return Err(Error::Syntax(format!("defprotocol: invalid or duplicate method {}", method)));
+            }
+            signatures.push(method);
+        }
+
+        let protocol = Rc::new(Protocol {
+            name: symbol.name.to_string(),
+            methods: signatures.iter().map(|m| m.name.to_string()).collect(),
+            impls: RefCell::new(HashMap::new()),
+        });
+
+        // Name => {:method function}, method => function
+        let mut members = vec![];
+        for method in signatures {
+            let function = Value::Function(Rc::new(ProtocolMethod {
+                protocol: protocol.clone(),
+                name: method.name.to_string(),
+            }));
+            environment.borrow_mut().insert(&method, function.clone())?;
+            members.push((
+                Value::Keyword(Keyword {
+                    name: format!(":{}", method.name),
+                }),
+                function,
+            ));
+        }
+
+        environment
+            .borrow()
+            .protocols
+            .borrow_mut()
+            .insert(protocol.name.clone(), protocol.clone());
+        environment.borrow_mut().insert(&symbol, Value::as_map(members)?)?;
+
+        Ok(Value::Symbol(symbol))
+    }
+}
+
+fn lookup_protocol(symbol: &Symbol, environment: &Rc<RefCell<Environment>>) -> Result<Rc<Protocol>> {
+    match environment.borrow().protocols.borrow().get(symbol.name.as_ref()) {
+        Some(protocol) => Ok(protocol.clone()),
+        None => Err(Error::Type(format!("{} is not a protocol", symbol))),
+    }
+}
+
+impl fmt::Display for DefprotocolMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: defprotocol>")
+    }
+}
+
+// extend-type
+pub static SYMBOL_EXTEND_TYPE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("extend-type"),
+    meta: Meta {
+        doc: Cow::Borrowed("Implement a protocol for a type: (extend-type vector Protocol (method [self ...] body) ...)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("extend-type"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendTypeMacro;
+
+impl Macro for ExtendTypeMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() < 2 {
+            return Err(arity_error_min(2, args.len()));
+        }
+
+        let type_name = match &args[0] {
+            Value::Symbol(sym) => sym.name.to_string(),
+            Value::Nil => TypeName::Nil.to_string(),
+            _ => return Err(Error::Type("extend-type: first argument must be a type name".to_string())),
+        };
+        let known = BUILTIN_TYPE_NAMES.iter().any(|t| t.to_string() == type_name)
+            || environment.borrow().get_struct(&type_name).is_some()
+            || environment.borrow().get_enum(&type_name).is_some();
+        if !known {
+            return Err(Error::Name(type_name));
+        }
+
+        let mut protocol: Option<Rc<Protocol>> = None;
+        for form in args[1..].iter() {
+            match form {
+                Value::Symbol(sym) => protocol = Some(lookup_protocol(sym, &environment)?),
+                Value::List(l) => {
+                    let protocol = match &protocol {
+                        Some(protocol) => protocol,
+                        None => return Err(Error::Syntax("extend-type: methods must follow a protocol".to_string())),
+                    };
+                    let method = match l.value.first() {
+                        Some(Value::Symbol(sym)) if protocol.has_method(&sym.name) => sym.name.to_string(),
+                        _ => {
+                            return Err(Error::Syntax(format!(
+                                "extend-type: {} is not a method of {}",
+                                l.value.first().unwrap_or(&Value::Nil),
+                                protocol.name
+                            )))
+                        }
+                    };
+                    if l.value.len() < 2 {
+                        return Err(Error::Syntax(format!("extend-type: {} has no parameters", method)));
+                    }
+                    let function = Rc::new(Lambda {
+                        arities: parse_arities(&l.value[1..], "extend-type", &environment)?,
+                        environment: environment.clone(),
+                    });
+                    protocol.implement(type_name.clone(), method, function);
+                }
+                _ => return Err(Error::Syntax("extend-type: expected a protocol or a method".to_string())),
+            }
+        }
+
+        Ok(Value::Nil)
+    }
+}
+
+impl fmt::Display for ExtendTypeMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: extend-type>")
+    }
+}
+
+// satisfies?
+pub static SYMBOL_SATISFIES: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("satisfies?"),
+    meta: Meta {
+        doc: Cow::Borrowed("Return true if the type of the value implements the protocol: (satisfies? Protocol x)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("satisfies?"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatisfiesMacro;
+
+impl Macro for SatisfiesMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let protocol = match &args[0] {
+            Value::Symbol(sym) => lookup_protocol(sym, &environment)?,
+            _ => return Err(Error::Type("satisfies?: first argument must be a protocol".to_string())),
+        };
+        let value = eval(args[1].clone(), environment, false)?;
+
+        Ok(Value::Bool(protocol.is_satisfied_by(&value)))
+    }
+}
+
+impl fmt::Display for SatisfiesMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: satisfies?>")
+    }
+}
+
+// typedef
+pub static SYMBOL_TYPEDEF: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("typedef"),
+    meta: Meta {
+        doc: Cow::Borrowed("Define a type alias used by annotations: (typedef #map-key (union #i64 #str #key))."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("typedef"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedefMacro;
+
+impl Macro for TypedefMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let name = match &args[0] {
+            Value::Type(Type::User(name)) => name.clone(),
+            Value::Type(t) => return Err(Error::Syntax(format!("typedef: cannot redefine {}", t))),
+            _ => {
+                return Err(Error::Syntax(
+                    "typedef: first argument must be a type name like #int".to_string(),
+                ))
+            }
+        };
+        if environment.borrow().typedefs.borrow().contains_key(&name) {
+            return Err(Error::Syntax(format!("typedef: cannot redefine {}", Type::User(name))));
+        }
+        if environment.borrow().get_struct(&name).is_some() {
+            return Err(Error::Syntax(format!("typedef: struct {} is already defined", name)));
+        }
+        if environment.borrow().get_enum(&name).is_some() {
+            return Err(Error::Syntax(format!("typedef: enum {} is already defined", name)));
+        }
+
+        // aliases are stored resolved, an annotation never sees the alias itself
+        let r#type = match eval(args[1].clone(), environment.clone(), false)? {
+            Value::Type(t) => t,
+            value => return Err(type_error("type", value.type_name().as_str())),
+        };
+        if r#type == Type::User(name.clone()) {
+            return Err(Error::Syntax(format!("typedef: #{} cannot refer to itself", name)));
+        }
+        environment.borrow().typedefs.borrow_mut().insert(name.clone(), r#type);
+
+        Ok(Value::Type(Type::User(name)))
+    }
+}
+
+impl fmt::Display for TypedefMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: typedef>")
+    }
+}
+
+// subtype?
+pub static SYMBOL_SUBTYPE: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("subtype?"),
+    meta: Meta {
+        doc: Cow::Borrowed("Return true if every value of the first type is also of the second type: (subtype? #i64 #number)."),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("subtype?"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtypeMacro;
+
+impl Macro for SubtypeMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 2 {
+            return Err(arity_error(2, args.len()));
+        }
+
+        let mut types = vec![];
+        for arg in args {
+            match eval(arg, environment.clone(), false)? {
+                Value::Type(t) => types.push(t),
+                value => return Err(type_error("type", value.type_name().as_str())),
+            }
+        }
+
+        // struct ancestors are looked up in the environment
+        let structs = environment.borrow().visible_structs();
+        let result = types[0].is_subtype(&types[1], &structs);
+        Ok(Value::Bool(result))
+    }
+}
+
+impl fmt::Display for SubtypeMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: subtype?>")
+    }
+}
+
+// import
+pub static SYMBOL_IMPORT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("import"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Import a module: (import \"lib/stats.sl\" :as st [mean]) binds st/name and the selected names, loaded once.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("import"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportMacro;
+
+impl Macro for ImportMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        let name = match &args[0] {
+            Value::String(s) => s.clone(),
+            Value::Symbol(sym) => sym.name.to_string(),
+            _ => return Err(Error::Type("import: module must be a string or a symbol".to_string())),
+        };
+
+        let mut alias = None;
+        let mut selected = vec![];
+        let mut rest = &args[1..];
+        while !rest.is_empty() {
+            match rest {
+                [Value::Keyword(k), Value::Symbol(sym), tail @ ..] if k.name == ":as" => {
+                    alias = Some(sym.clone());
+                    rest = tail;
+                }
+                [Value::Vector(v), tail @ ..] => {
+                    for name in v.value.iter() {
+                        match name {
+                            Value::Symbol(sym) => selected.push(sym.clone()),
+                            _ => return Err(Error::Syntax("import: selected names must be symbols".to_string())),
+                        }
+                    }
+                    rest = tail;
+                }
+                _ => return Err(Error::Syntax("import: expected :as alias or a vector of names".to_string())),
+            }
+        }
+
+        let dir = environment.borrow().namespace.borrow().dir.clone();
+        let module = load(&resolve(&name, dir.as_deref())?)?;
+
+        for sym in selected.iter() {
+            let (_, value) = match module.get(&sym.name)? {
+                Some(found) => found,
+                None => return Err(Error::Name(format!("{}/{}", module.name, sym))),
+            };
+            environment.borrow_mut().insert(sym, value)?;
+        }
+
+        let alias = alias.unwrap_or_else(|| runtime_symbol(&module.name));
+        environment
+            .borrow()
+            .namespace
+            .borrow_mut()
+            .aliases
+            .insert(alias.name.to_string(), module);
+
+        Ok(Value::Symbol(alias))
+    }
+}
+
+impl fmt::Display for ImportMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: import>")
+    }
+}
+
+// export
+pub static SYMBOL_EXPORT: Lazy<Symbol> = Lazy::new(|| Symbol {
+    name: Cow::Borrowed("export"),
+    meta: Meta {
+        doc: Cow::Borrowed(
+            "Export bindings of the file: (export [name ...]). Without export, everything defined in the file is exported.",
+        ),
+        mutable: false,
+        arglists: vec![],
+    },
+    hash: fxhash::hash("export"),
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportMacro;
+
+impl Macro for ExportMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+
+        let names = match &args[0] {
+            Value::Vector(v) => v
+                .value
+                .iter()
+                .map(|name| match name {
+                    Value::Symbol(sym) => Ok(sym.name.to_string()),
+                    _ => Err(Error::Syntax("export: names must be symbols".to_string())),
+                })
+                .collect::<Result<Vec<String>>>()?,
+            _ => return Err(Error::Syntax("export: argument must be a vector of names".to_string())),
+        };
+
+        let namespace = environment.borrow().namespace.clone();
+        namespace.borrow_mut().exports.get_or_insert_with(Vec::new).extend(names);
+
+        Ok(Value::Nil)
+    }
+}
+
+impl fmt::Display for ExportMacro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<builtin macro: export>")
+    }
+}
+
 // TODO:
-// MacroMacro,
-// /AUTO-GENSYM
-// Try-Catch-Finally
-// EnumMacro,
-// StructMacro,
-// ClassMacro,
-// NameSpaceMacro
+// ClassMacro
@@ -0,0 +1,897 @@
+/* core/check.rs */
+
+// static gradual type checking (scilisp check --types)
+// Types are inferred from literals, builtin signatures and annotations. Anything else is #any,
+// which matches everything, so only definite mismatches are reported.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::core::destructure::split_annotations;
+use crate::core::environment::Environment;
+use crate::core::types::error::Error;
+use crate::core::types::lambda::is_anpersand;
+use crate::core::types::r#type::Type;
+use crate::core::types::vector::Vector;
+use crate::core::value::Value;
+
+// an error found at the top-level form starting on the line
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub error: Error,
+}
+
+// one arity of a function
+#[derive(Debug, Clone)]
+struct Signature {
+    params: Vec<Type>,
+    rest: Option<Type>,
+    ret: Type,
+}
+
+impl Signature {
+    fn fixed(params: Vec<Type>, ret: Type) -> Signature {
+        Signature { params, rest: None, ret }
+    }
+
+    fn variadic(params: Vec<Type>, rest: Type, ret: Type) -> Signature {
+        Signature {
+            params,
+            rest: Some(rest),
+            ret,
+        }
+    }
+
+    fn accepts(&self, argc: usize) -> bool {
+        match self.rest {
+            Some(_) => argc >= self.params.len(),
+            None => argc == self.params.len(),
+        }
+    }
+
+    fn param(&self, i: usize) -> &Type {
+        self.params.get(i).or(self.rest.as_ref()).unwrap_or(&Type::Any)
+    }
+
+    // the first argument that cannot match its parameter
+    fn mismatch(&self, args: &[Type]) -> Option<usize> {
+        args.iter().enumerate().position(|(i, arg)| !overlaps(arg, self.param(i)))
+    }
+
+    fn is_annotated(&self) -> bool {
+        self.params.iter().any(|t| *t != Type::Any)
+    }
+
+    fn describe(&self) -> String {
+        match self.rest {
+            Some(_) => format!("at least {}", self.params.len()),
+            None => self.params.len().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    r#type: Type,
+    arities: Vec<Signature>, // empty if the signature is not known
+    declared: bool,          // annotated, so that set! is checked too
+}
+
+impl Binding {
+    fn value(r#type: Type) -> Binding {
+        Binding {
+            r#type,
+            arities: vec![],
+            declared: false,
+        }
+    }
+
+    fn function(arities: Vec<Signature>) -> Binding {
+        Binding {
+            r#type: Type::Fn,
+            arities,
+            declared: false,
+        }
+    }
+}
+
+// whether a value of the actual type can be of the expected type
+fn overlaps(actual: &Type, expected: &Type) -> bool {
+    match (actual, expected) {
+        (Type::Any, _) | (_, Type::Any) => true,
+        // struct ancestors are not known statically
+        (Type::User(_), _) | (_, Type::User(_)) => true,
+        (Type::Union(ts), e) => ts.iter().any(|t| overlaps(t, e)),
+        (a, Type::Union(ts)) => ts.iter().any(|t| overlaps(a, t)),
+        (Type::Option(t), e) => overlaps(&Type::Nil, e) || overlaps(t, e),
+        (a, Type::Option(t)) => *a == Type::Nil || overlaps(a, t),
+        (Type::Of(ab, ae), Type::Of(eb, ee)) => overlaps(ab, eb) && overlaps(ae, ee),
+        (Type::Of(ab, _), e) => overlaps(ab, e),
+        (a, Type::Of(eb, _)) => overlaps(a, eb),
+        (Type::Signature(_, _), e) => overlaps(&Type::Fn, e),
+        (a, Type::Signature(_, _)) => overlaps(a, &Type::Fn),
+        (a, e) => a.supertypes().contains(e) || e.supertypes().contains(a),
+    }
+}
+
+fn is_number(t: &Type) -> bool {
+    matches!(t, Type::I64 | Type::F64 | Type::Number)
+}
+
+// type of either branch
+fn join(a: Type, b: Type) -> Type {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (a, b) if is_number(&a) && is_number(&b) => Type::Number,
+        _ => Type::Any,
+    }
+}
+
+// [1 2 3] => #v[#i64]
+fn collection(base: Type, elements: Vec<Type>) -> Type {
+    match elements.split_first() {
+        Some((first, rest)) if *first != Type::Any && rest.iter().all(|t| t == first) => {
+            Type::Of(Box::new(base), Box::new(first.clone()))
+        }
+        _ => base,
+    }
+}
+
+// (+ 1 2) => #i64, (+ 1 2.0) => #f64
+const NUMERIC: [&str; 6] = ["+", "-", "*", "inc", "dec", "abs"];
+
+fn numeric_result(args: &[Type]) -> Type {
+    if args.iter().all(|t| *t == Type::I64) {
+        Type::I64
+    } else if args.iter().all(|t| matches!(t, Type::I64 | Type::F64)) {
+        Type::F64
+    } else {
+        Type::Number
+    }
+}
+
+fn builtin_signatures() -> HashMap<&'static str, Vec<Signature>> {
+    use Type::{Any, Bool, Collection, Fn, Iterable, Nil, Number, Str, F64, I64};
+
+    let mut table = HashMap::new();
+    for name in ["+", "*"] {
+        table.insert(name, vec![Signature::variadic(vec![], Number, Number)]);
+    }
+    table.insert("-", vec![Signature::variadic(vec![Number], Number, Number)]);
+    table.insert("/", vec![Signature::variadic(vec![Number, Number], Number, F64)]);
+    table.insert("//", vec![Signature::variadic(vec![Number, Number], Number, Number)]);
+    table.insert("%", vec![Signature::fixed(vec![Number, Number], Number)]);
+    for name in ["inc", "dec", "abs"] {
+        table.insert(name, vec![Signature::fixed(vec![Number], Number)]);
+    }
+    for name in ["sqrt", "cos", "sin", "tan", "acos", "asin", "atan", "ln", "log10"] {
+        table.insert(name, vec![Signature::fixed(vec![Number], F64)]);
+    }
+    table.insert("log", vec![Signature::fixed(vec![Number, Number], F64)]);
+    table.insert("rand", vec![Signature::fixed(vec![], F64)]);
+    table.insert("randint", vec![Signature::fixed(vec![I64, I64], I64)]);
+
+    for name in ["=", "!=", "<", ">", "<=", ">="] {
+        table.insert(name, vec![Signature::variadic(vec![Any], Any, Bool)]);
+    }
+    table.insert("is", vec![Signature::fixed(vec![Any, Any], Bool)]);
+    for name in [
+        "not", "nil?", "true?", "false?", "number?", "i64?", "f64?", "empty?", "string?", "keyword?", "symbol?", "list?",
+        "vector?", "map?", "set?",
+    ] {
+        table.insert(name, vec![Signature::fixed(vec![Any], Bool)]);
+    }
+    table.insert("zero?", vec![Signature::fixed(vec![Number], Bool)]);
+    for name in ["even?", "odd?"] {
+        table.insert(name, vec![Signature::fixed(vec![I64], Bool)]);
+    }
+
+    table.insert("print", vec![Signature::variadic(vec![], Any, Nil)]);
+    table.insert("type", vec![Signature::fixed(vec![Any], Str)]);
+    table.insert("type-of", vec![Signature::fixed(vec![Any], Type::Kind)]);
+    table.insert("isa?", vec![Signature::fixed(vec![Any, Type::Kind], Bool)]);
+    table.insert("str", vec![Signature::fixed(vec![Any], Str)]);
+    table.insert("i64", vec![Signature::fixed(vec![Any], I64)]);
+    table.insert("f64", vec![Signature::fixed(vec![Any], F64)]);
+
+    table.insert("atom", vec![Signature::fixed(vec![Any], Type::Atom)]);
+    table.insert("deref", vec![Signature::fixed(vec![Type::Atom], Any)]);
+    table.insert("swap!", vec![Signature::variadic(vec![Type::Atom, Fn], Any, Any)]);
+    table.insert("reset!", vec![Signature::fixed(vec![Type::Atom, Any], Any)]);
+    table.insert("compare-and-set!", vec![Signature::fixed(vec![Type::Atom, Any, Any], Bool)]);
+    table.insert("add-watch", vec![Signature::fixed(vec![Type::Atom, Any, Fn], Type::Atom)]);
+    table.insert("remove-watch", vec![Signature::fixed(vec![Type::Atom, Any], Bool)]);
+
+    table.insert("partial", vec![Signature::variadic(vec![Fn], Any, Fn)]);
+    table.insert("comp", vec![Signature::variadic(vec![], Fn, Fn)]);
+    table.insert("juxt", vec![Signature::variadic(vec![Fn], Fn, Fn)]);
+    table.insert("complement", vec![Signature::fixed(vec![Fn], Fn)]);
+    table.insert("constantly", vec![Signature::fixed(vec![Any], Fn)]);
+    table.insert("identity", vec![Signature::fixed(vec![Any], Any)]);
+    table.insert("fnil", vec![Signature::variadic(vec![Fn, Any], Any, Fn)]);
+    table.insert(
+        "memoize",
+        vec![Signature::fixed(vec![Fn], Fn), Signature::fixed(vec![Fn, I64], Fn)],
+    );
+
+    table.insert("len", vec![Signature::fixed(vec![Iterable], I64)]);
+    table.insert("sum", vec![Signature::fixed(vec![Iterable], F64)]);
+    table.insert("mean", vec![Signature::fixed(vec![Iterable], F64)]);
+    for name in ["max", "min"] {
+        table.insert(name, vec![Signature::fixed(vec![Iterable], Any)]);
+    }
+
+    for name in [
+        "trim",
+        "upper-case",
+        "lower-case",
+        "lower-camel",
+        "upper-camel",
+        "snake-case",
+        "kebab-case",
+        "title-case",
+        "train-case",
+        "shouty-snake",
+        "shouty-kebab",
+    ] {
+        table.insert(name, vec![Signature::fixed(vec![Str], Str)]);
+    }
+    table.insert("repeat", vec![Signature::fixed(vec![Str, I64], Str)]);
+    table.insert("join", vec![Signature::fixed(vec![Collection, Str], Str)]);
+    table.insert(
+        "split",
+        vec![
+            Signature::fixed(vec![Str, Str], Any),
+            Signature::fixed(vec![Str, Str, Fn], Any),
+        ],
+    );
+
+    table
+}
+
+// ([params] => #ret body ...) of fn and defn, with the parameter patterns and the body
+fn parse_arities(forms: &[Value], aliases: &HashMap<String, Type>) -> Vec<(Signature, Vec<Value>, Vec<Value>)> {
+    let arity = |params: &Vector, body: &[Value]| {
+        let (params, types) = split_annotations(params, "check").ok()?;
+        let types = types.iter().map(|t| t.resolve(aliases)).collect();
+        let (ret, body) = match body {
+            [Value::Symbol(arrow), Value::Type(ret), body @ ..] if arrow.name == "=>" => (ret.resolve(aliases), body),
+            body => (Type::Any, body),
+        };
+        let rest = params.value.iter().any(is_anpersand).then_some(Type::Any);
+        Some((
+            Signature {
+                params: types,
+                rest,
+                ret,
+            },
+            params.value,
+            body.to_vec(),
+        ))
+    };
+
+    match forms.first() {
+        Some(Value::Vector(params)) => arity(params, &forms[1..]).into_iter().collect(),
+        Some(Value::List(_)) => forms
+            .iter()
+            .filter_map(|form| match form {
+                Value::List(l) => match l.value.split_first() {
+                    Some((Value::Vector(params), body)) => arity(params, body),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn contains_return(forms: &[Value]) -> bool {
+    forms.iter().any(|form| match form {
+        Value::Symbol(sym) => sym.name == "return",
+        Value::List(l) => contains_return(&l.value),
+        Value::Vector(v) => contains_return(&v.value),
+        _ => false,
+    })
+}
+
+// names whose type can change: targets of set! and the destructive macros, or defined twice
+fn collect_reassigned(form: &Value, defined: &mut HashSet<String>, reassigned: &mut HashSet<String>) {
+    let items = match form {
+        Value::List(l) => &l.value,
+        Value::Vector(v) => &v.value,
+        _ => return,
+    };
+
+    if let [Value::Symbol(head), Value::Symbol(target), ..] = &items[..] {
+        match head.name.as_ref() {
+            "set!" | "insert!" | "remove!" | "replace!" => {
+                reassigned.insert(target.name.to_string());
+            }
+            "def" if !defined.insert(target.name.to_string()) => {
+                reassigned.insert(target.name.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    for item in items {
+        collect_reassigned(item, defined, reassigned);
+    }
+}
+
+struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    aliases: HashMap<String, Type>, // typedef
+    reassigned: HashSet<String>,
+    line: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+pub fn check_types(forms: &[(usize, Value)]) -> Vec<Diagnostic> {
+    let mut defined = HashSet::new();
+    let mut reassigned = HashSet::new();
+    for (_, form) in forms {
+        collect_reassigned(form, &mut defined, &mut reassigned);
+    }
+
+    // builtin functions and constants (macros are handled by the forms below or skipped)
+    let signatures = builtin_signatures();
+    let environment = Environment::new_root_environment();
+    let mut root = HashMap::new();
+    for (key, value) in environment.borrow().current.borrow().iter() {
+        let binding = match value {
+            Value::Macro(_) => continue,
+            Value::Function(_) => Binding::function(signatures.get(key.name.as_ref()).cloned().unwrap_or_default()),
+            value => Binding::value(Type::lineage(value)[0].clone()),
+        };
+        root.insert(key.name.to_string(), binding);
+    }
+
+    let mut checker = Checker {
+        scopes: vec![root, HashMap::new()],
+        aliases: HashMap::new(),
+        reassigned,
+        line: 0,
+        diagnostics: vec![],
+    };
+    for (line, form) in forms {
+        checker.line = *line;
+        checker.infer(form);
+    }
+    checker.diagnostics
+}
+
+// (loop [pattern value ...] ...), (for [pattern coll ...] ...), (match expr pattern [:when guard] body ...)
+fn binding_patterns<'a>(head: &str, args: &'a [Value]) -> Vec<&'a Value> {
+    match (head, args) {
+        ("loop" | "for", [Value::Vector(bindings), ..]) => bindings.value.iter().step_by(2).collect(),
+        ("match", [_, clauses @ ..]) => {
+            let mut patterns = vec![];
+            let mut rest = clauses;
+            while let Some((pattern, tail)) = rest.split_first() {
+                patterns.push(pattern);
+                rest = match tail {
+                    [Value::Keyword(k), _, tail @ ..] if k.name == ":when" => tail,
+                    tail => tail,
+                };
+                rest = rest.get(1..).unwrap_or(&[]);
+            }
+            patterns
+        }
+        _ => vec![],
+    }
+}
+
+impl Checker {
+    fn report(&mut self, error: Error) {
+        self.diagnostics.push(Diagnostic { line: self.line, error });
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn bind(&mut self, name: &str, binding: Binding) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), binding);
+    }
+
+    // every symbol of a binding pattern (or of the arguments of an unknown macro) is #any
+    fn shadow(&mut self, form: &Value) {
+        match form {
+            Value::Symbol(sym) if is_anpersand(form) || sym.name == "_" => {}
+            // builtins are rarely rebound outside binding patterns, keep checking calls to them
+            Value::Symbol(sym) if self.scopes[0].contains_key(sym.name.as_ref()) => {}
+            Value::Symbol(sym) => self.bind(&sym.name.clone(), Binding::value(Type::Any)),
+            Value::List(l) => {
+                if let Some(Value::List(_)) = l.value.first() {
+                    self.shadow(&l.value[0]);
+                }
+                l.value.iter().skip(1).for_each(|v| self.shadow(v));
+            }
+            Value::Vector(v) => v.value.iter().for_each(|v| self.shadow(v)),
+            Value::Set(s) => s.value.iter().for_each(|v| self.shadow(v)),
+            Value::Map(m) => m.value.iter().for_each(|(k, v)| {
+                self.shadow(k);
+                self.shadow(v);
+            }),
+            _ => {}
+        }
+    }
+
+    fn infer_all(&mut self, forms: &[Value]) -> Vec<Type> {
+        forms.iter().map(|form| self.infer(form)).collect()
+    }
+
+    fn infer_body(&mut self, forms: &[Value]) -> Type {
+        self.infer_all(forms).pop().unwrap_or(Type::Nil)
+    }
+
+    fn infer(&mut self, form: &Value) -> Type {
+        match form {
+            Value::Nil => Type::Nil,
+            Value::Bool(_) => Type::Bool,
+            Value::I64(_) => Type::I64,
+            Value::F64(_) => Type::F64,
+            Value::String(_) => Type::Str,
+            Value::Keyword(_) => Type::Key,
+            Value::Regex(_) => Type::Regex,
+            Value::Type(_) => Type::Kind,
+            Value::Symbol(sym) => self.lookup(&sym.name).map(|b| b.r#type.clone()).unwrap_or(Type::Any),
+            Value::Vector(v) => {
+                let elements = self.infer_all(&v.value);
+                collection(Type::Vector, elements)
+            }
+            Value::Set(s) => {
+                let elements = s.value.iter().map(|v| self.infer(v)).collect();
+                collection(Type::Set, elements)
+            }
+            Value::Map(m) => {
+                let mut values = vec![];
+                for (k, v) in m.value.iter() {
+                    self.infer(k);
+                    values.push(self.infer(v));
+                }
+                collection(Type::Map, values)
+            }
+            Value::List(l) => self.infer_list(&l.value),
+            _ => Type::Any,
+        }
+    }
+
+    fn infer_list(&mut self, forms: &[Value]) -> Type {
+        let (head, args) = match forms.split_first() {
+            Some((Value::Symbol(head), args)) => (head.name.to_string(), args),
+            Some((head, args)) => {
+                self.infer(head);
+                self.infer_all(args);
+                return Type::Any;
+            }
+            None => return Type::List,
+        };
+
+        match head.as_str() {
+            "quote" | "syntax-quote" | "macroexpand-1" | "macroexpand" | "macroexpand-all" | "doc" | "import" | "export" => {
+                Type::Any
+            }
+            "def" | "const" => self.infer_def(args, head == "const"),
+            "def-dynamic" => self.infer_def_dynamic(args),
+            "defn" => self.infer_defn(args),
+            "defgen" => self.infer_defgen(args),
+            "fn" => {
+                self.infer_function("fn", args);
+                Type::Fn
+            }
+            "macro" => {
+                if let Some(Value::Symbol(sym)) = args.first() {
+                    self.bind(&sym.name.clone(), Binding::value(Type::Macro));
+                }
+                Type::Sym
+            }
+            "let" => self.infer_let(args),
+            "binding" => self.infer_binding(args),
+            "do" => self.infer_body(args),
+            "if" => {
+                let types = self.infer_all(args);
+                let then = types.get(1).cloned().unwrap_or(Type::Nil);
+                let otherwise = types.get(2).cloned().unwrap_or(Type::Nil);
+                join(then, otherwise)
+            }
+            "set!" => self.infer_set(args),
+            "typedef" => {
+                if let [Value::Type(Type::User(name)), r#type] = args {
+                    if let Some(t) = self.static_type(r#type) {
+                        self.aliases.insert(name.clone(), t);
+                    }
+                }
+                Type::Kind
+            }
+            "->" | "->>" => self.infer_thread(args, head == "->"),
+            _ => match self.lookup(&head).cloned() {
+                // user macros may rewrite their arguments
+                Some(binding) if binding.r#type == Type::Macro => Type::Any,
+                Some(binding) => self.infer_call(&head, &binding, args),
+                None => {
+                    self.infer_opaque(&head, args);
+                    Type::Any
+                }
+            },
+        }
+    }
+
+    // #int or (union #i64 #str) written in the source
+    fn static_type(&self, form: &Value) -> Option<Type> {
+        match form {
+            Value::Type(t) => Some(t.resolve(&self.aliases)),
+            Value::List(l) => match l.value.split_first() {
+                Some((Value::Symbol(head), types)) if head.name == "union" => types
+                    .iter()
+                    .map(|t| self.static_type(t))
+                    .collect::<Option<Vec<Type>>>()
+                    .map(Type::union),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // every symbol of a loop, for or match pattern is a local #any, also where it names a builtin
+    fn shadow_pattern(&mut self, pattern: &Value) {
+        match pattern {
+            Value::Symbol(sym) if is_anpersand(pattern) || sym.name == "_" => {}
+            Value::Symbol(sym) => self.bind(&sym.name.clone(), Binding::value(Type::Any)),
+            // (pred? x), (Variant x ...): the head is not bound
+            Value::List(l) => l.value.iter().skip(1).for_each(|v| self.shadow_pattern(v)),
+            Value::Vector(v) => v.value.iter().for_each(|v| self.shadow_pattern(v)),
+            Value::Map(m) => m.value.values().for_each(|v| self.shadow_pattern(v)),
+            _ => {}
+        }
+    }
+
+    // unknown macros (for, match, try, ...) and functions: calls inside are still checked
+    fn infer_opaque(&mut self, head: &str, args: &[Value]) {
+        self.scopes.push(HashMap::new());
+        args.iter().for_each(|arg| self.shadow(arg));
+        for pattern in binding_patterns(head, args) {
+            self.shadow_pattern(pattern);
+        }
+        for arg in args {
+            match arg {
+                Value::List(_) | Value::Vector(_) | Value::Map(_) | Value::Set(_) => {
+                    self.infer(arg);
+                }
+                _ => {}
+            }
+        }
+        self.scopes.pop();
+    }
+
+    fn infer_call(&mut self, name: &str, binding: &Binding, args: &[Value]) -> Type {
+        let types = self.infer_all(args);
+        let arities = match (&binding.r#type, &binding.arities[..]) {
+            (Type::Signature(params, ret), []) => vec![Signature::fixed(params.clone(), (**ret).clone())],
+            (_, []) => return Type::Any,
+            (_, arities) => arities.to_vec(),
+        };
+
+        let candidates = arities.iter().filter(|s| s.accepts(types.len())).collect::<Vec<&Signature>>();
+        if candidates.is_empty() {
+            let mut accepted = arities.iter().map(|s| s.describe()).collect::<Vec<String>>();
+            accepted.dedup();
+            let unit = if accepted == ["1"] { "argument" } else { "arguments" };
+            self.report(Error::Arity(format!(
+                "{} takes {} {}, got {}",
+                name,
+                accepted.join(" or "),
+                unit,
+                types.len()
+            )));
+            return Type::Any;
+        }
+
+        let matching = candidates
+            .iter()
+            .filter(|s| s.mismatch(&types).is_none())
+            .collect::<Vec<&&Signature>>();
+        match (&matching[..], &candidates[..]) {
+            ([sig], _) if sig.ret == Type::Number && NUMERIC.contains(&name) => numeric_result(&types),
+            ([sig], _) => sig.ret.clone(),
+            ([], [sig]) => {
+                let i = sig.mismatch(&types).unwrap();
+                self.report(Error::Type(format!(
+                    "argument {} of {} expected {}, got {}",
+                    i + 1,
+                    name,
+                    sig.param(i),
+                    types[i]
+                )));
+                Type::Any
+            }
+            ([], _) => {
+                let types = types.iter().map(|t| t.to_string()).collect::<Vec<String>>();
+                self.report(Error::Type(format!("no method of {} matches ({})", name, types.join(", "))));
+                Type::Any
+            }
+            (matching, _) => matching.iter().map(|s| s.ret.clone()).reduce(join).unwrap_or(Type::Any),
+        }
+    }
+
+    // type and signature of a bound value
+    fn infer_value(&mut self, form: &Value) -> (Type, Vec<Signature>) {
+        match form {
+            Value::List(l) if matches!(l.value.first(), Some(Value::Symbol(s)) if s.name == "fn") => {
+                (Type::Fn, self.infer_function("fn", &l.value[1..]))
+            }
+            Value::Symbol(sym) => match self.lookup(&sym.name) {
+                Some(binding) => (binding.r#type.clone(), binding.arities.clone()),
+                None => (Type::Any, vec![]),
+            },
+            form => (self.infer(form), vec![]),
+        }
+    }
+
+    // (def name #type "doc" value)
+    fn infer_def(&mut self, args: &[Value], constant: bool) -> Type {
+        let (args, annotation) = match args {
+            [name, Value::Type(t), rest @ ..] if !rest.is_empty() => {
+                ([std::slice::from_ref(name), rest].concat(), Some(t.resolve(&self.aliases)))
+            }
+            args => (args.to_vec(), None),
+        };
+        let name = match args.first() {
+            Some(Value::Symbol(sym)) => sym.name.to_string(),
+            _ => return Type::Any,
+        };
+
+        let (r#type, arities) = match &args[..] {
+            [_] => (Type::Nil, vec![]),
+            [_, value] | [_, _, value] => self.infer_value(value),
+            _ => return Type::Any,
+        };
+
+        let binding = match annotation {
+            Some(expected) => {
+                if !overlaps(&r#type, &expected) {
+                    self.report(Error::Type(format!("binding {} expected {}, got {}", name, expected, r#type)));
+                }
+                Binding {
+                    r#type: expected,
+                    arities,
+                    declared: true,
+                }
+            }
+            None if constant || !self.reassigned.contains(&name) => Binding {
+                r#type,
+                arities,
+                declared: false,
+            },
+            None => Binding::value(Type::Any),
+        };
+        self.bind(&name, binding);
+        Type::Sym
+    }
+
+    // an unannotated dynamic variable may be rebound to a value of any type
+    fn infer_def_dynamic(&mut self, args: &[Value]) -> Type {
+        let r#type = self.infer_def(args, false);
+        if let Some(Value::Symbol(sym)) = args.first() {
+            if self.lookup(&sym.name).is_some_and(|b| !b.declared) {
+                self.bind(&sym.name.clone(), Binding::value(Type::Any));
+            }
+        }
+        r#type
+    }
+
+    // (defn name "doc" [params] => #ret body ...)
+    fn infer_defn(&mut self, args: &[Value]) -> Type {
+        let name = match args.first() {
+            Some(Value::Symbol(sym)) => sym.name.to_string(),
+            _ => return Type::Any,
+        };
+        let rest = match &args[1..] {
+            [Value::String(_), rest @ ..] => rest,
+            rest => rest,
+        };
+
+        let parsed = parse_arities(rest, &self.aliases);
+        let mut arities = parsed.iter().map(|(s, _, _)| s.clone()).collect::<Vec<Signature>>();
+
+        // annotated methods are added to the generic function
+        let existing = self
+            .scopes
+            .last()
+            .unwrap()
+            .get(&name)
+            .map(|b| b.arities.clone())
+            .unwrap_or_default();
+        let generic = existing.iter().chain(arities.iter()).any(|s| s.is_annotated());
+        if generic {
+            arities = existing.into_iter().chain(arities).collect();
+        }
+
+        let reassigned = self.reassigned.contains(&name);
+        let binding = match reassigned {
+            true => Binding::value(Type::Fn),
+            false => Binding::function(arities),
+        };
+        self.bind(&name, binding);
+
+        let types = parsed
+            .iter()
+            .map(|(sig, params, body)| self.infer_arity(&name, sig, params, body))
+            .collect::<Vec<Type>>();
+
+        // the return type of an unannotated single arity is inferred from its body
+        if let ([(sig, _, body)], [r#type], false, false) = (&parsed[..], &types[..], generic, reassigned) {
+            if sig.ret == Type::Any && !contains_return(body) {
+                if let Some(binding) = self.scopes.last_mut().unwrap().get_mut(&name) {
+                    binding.arities[0].ret = r#type.clone();
+                }
+            }
+        }
+        Type::Sym
+    }
+
+    // a call returns a generator, the body is checked without its return value
+    fn infer_defgen(&mut self, args: &[Value]) -> Type {
+        let name = match args.first() {
+            Some(Value::Symbol(sym)) => sym.name.to_string(),
+            _ => return Type::Any,
+        };
+        let rest = match &args[1..] {
+            [Value::String(_), rest @ ..] => rest,
+            rest => rest,
+        };
+
+        let parsed = parse_arities(rest, &self.aliases);
+        let arities = parsed
+            .iter()
+            .map(|(sig, _, _)| Signature {
+                ret: Type::Generator,
+                ..sig.clone()
+            })
+            .collect::<Vec<Signature>>();
+        let binding = match self.reassigned.contains(&name) {
+            true => Binding::value(Type::Fn),
+            false => Binding::function(arities),
+        };
+        self.bind(&name, binding);
+
+        for (sig, params, body) in parsed.iter() {
+            let sig = Signature {
+                ret: Type::Any,
+                ..sig.clone()
+            };
+            self.infer_arity(&name, &sig, params, body);
+        }
+        Type::Sym
+    }
+
+    fn infer_function(&mut self, name: &str, args: &[Value]) -> Vec<Signature> {
+        let parsed = parse_arities(args, &self.aliases);
+        for (sig, params, body) in parsed.iter() {
+            self.infer_arity(name, sig, params, body);
+        }
+        parsed.into_iter().map(|(s, _, _)| s).collect()
+    }
+
+    fn infer_arity(&mut self, name: &str, sig: &Signature, params: &[Value], body: &[Value]) -> Type {
+        self.scopes.push(HashMap::new());
+        for (param, r#type) in params.iter().zip(sig.params.iter()) {
+            match param {
+                Value::Symbol(sym) => self.bind(
+                    &sym.name.clone(),
+                    Binding {
+                        r#type: r#type.clone(),
+                        arities: vec![],
+                        declared: *r#type != Type::Any,
+                    },
+                ),
+                pattern => self.shadow(pattern),
+            }
+        }
+        params.iter().skip(sig.params.len()).for_each(|p| self.shadow(p));
+
+        let r#type = self.infer_body(body);
+        if !overlaps(&r#type, &sig.ret) {
+            self.report(Error::Type(format!(
+                "return value of {} expected {}, got {}",
+                name, sig.ret, r#type
+            )));
+        }
+        self.scopes.pop();
+        r#type
+    }
+
+    fn infer_let(&mut self, args: &[Value]) -> Type {
+        self.scopes.push(HashMap::new());
+        let body = match args.split_first() {
+            Some((Value::Vector(bindings), body)) => {
+                for pair in bindings.value.chunks(2) {
+                    if let [pattern, value] = pair {
+                        let (r#type, arities) = self.infer_value(value);
+                        match pattern {
+                            Value::Symbol(sym) if !self.reassigned.contains(sym.name.as_ref()) => {
+                                self.bind(
+                                    &sym.name.clone(),
+                                    Binding {
+                                        r#type,
+                                        arities,
+                                        declared: false,
+                                    },
+                                );
+                            }
+                            Value::Symbol(sym) => self.bind(&sym.name.clone(), Binding::value(Type::Any)),
+                            pattern => self.shadow(pattern),
+                        }
+                    }
+                }
+                body
+            }
+            _ => args,
+        };
+        let r#type = self.infer_body(body);
+        self.scopes.pop();
+        r#type
+    }
+
+    fn infer_set(&mut self, args: &[Value]) -> Type {
+        match args {
+            [Value::Symbol(sym), value] => {
+                let r#type = self.infer(value);
+                if let Some(binding) = self.lookup(&sym.name) {
+                    if binding.declared && !overlaps(&r#type, &binding.r#type) {
+                        let expected = binding.r#type.clone();
+                        self.report(Error::Type(format!("binding {} expected {}, got {}", sym, expected, r#type)));
+                    }
+                }
+            }
+            args => {
+                self.infer_all(args);
+            }
+        }
+        Type::Any
+    }
+
+    // each rebinding is checked like set!
+    fn infer_binding(&mut self, args: &[Value]) -> Type {
+        let body = match args.split_first() {
+            Some((Value::Vector(bindings), body)) => {
+                for pair in bindings.value.chunks(2) {
+                    self.infer_set(pair);
+                }
+                body
+            }
+            _ => args,
+        };
+        self.infer_body(body)
+    }
+
+    // (-> x (f a)) => (f x a), (->> x (f a)) => (f a x)
+    fn infer_thread(&mut self, args: &[Value], first: bool) -> Type {
+        let (init, forms) = match args.split_first() {
+            Some(split) => split,
+            None => return Type::Any,
+        };
+
+        let mut threaded = init.clone();
+        for form in forms {
+            let items = match form {
+                Value::List(l) if !l.value.is_empty() => {
+                    let mut items = l.value.clone();
+                    match first {
+                        true => items.insert(1, threaded),
+                        false => items.push(threaded),
+                    }
+                    items
+                }
+                f => vec![f.clone(), threaded],
+            };
+            threaded = match Value::as_list(items) {
+                Ok(list) => list,
+                Err(_) => return Type::Any,
+            };
+        }
+        self.infer(&threaded)
+    }
+}
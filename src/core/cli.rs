@@ -1,3 +1,4 @@
 /* core/cli.rs */
 
+pub mod check;
 pub mod repl;
@@ -0,0 +1,34 @@
+/* core/cli/check.rs */
+
+use std::path::PathBuf;
+
+use crate::core::check::check_types;
+use crate::core::parse::parse;
+use crate::core::read::read_lines;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::utility::try_read_file;
+
+// check a script without running it: syntax, then types with --types
+pub fn check(file: PathBuf, types: bool) -> Result<()> {
+    let content = try_read_file(&Some(file.clone()))?;
+    let parsed = parse(&content)?;
+    let forms = read_lines(parsed)?;
+
+    if types {
+        let diagnostics = check_types(&forms);
+        for diagnostic in diagnostics.iter() {
+            eprintln!("{}:{}: {}", file.display(), diagnostic.line, diagnostic.error);
+        }
+        if !diagnostics.is_empty() {
+            return Err(Error::Type(format!(
+                "{} error(s) found in {}",
+                diagnostics.len(),
+                file.display()
+            )));
+        }
+    }
+
+    println!("{}: ok", file.display());
+    Ok(())
+}
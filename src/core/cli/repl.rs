@@ -178,8 +178,17 @@ pub fn execute(file: Option<PathBuf>) -> Result<()> {
     let mut ast = Vec::<Value>::new();
     read(&mut ast, parsed)?;
 
-    // Eval
+    // Eval (imports are resolved from the directory of the file)
     let environment = Environment::new_root_environment();
+    // a bare file name has the empty parent ""
+    let dir = file
+        .as_ref()
+        .and_then(|f| f.parent())
+        .map(|d| match d.as_os_str().is_empty() {
+            true => PathBuf::from("."),
+            false => d.to_path_buf(),
+        });
+    environment.borrow().namespace.borrow_mut().dir = dir;
     eval_ast(ast, environment)?;
 
     Ok(())
@@ -0,0 +1,196 @@
+/* core/destructure.rs */
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::environment::Environment;
+use crate::core::eval::eval;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::lambda::{is_anpersand, SYMBOL_ANPERSAND};
+use crate::core::types::r#type::Type;
+use crate::core::types::vector::Vector;
+use crate::core::value::Value;
+
+fn is_default_key(value: &Value) -> bool {
+    matches!(value, Value::Keyword(k) if k.name == ":default")
+}
+
+fn invalid_pattern(form: &str, pattern: &Value) -> Error {
+    Error::Syntax(format!("{}: invalid binding pattern {}", form, pattern))
+}
+
+// symbol, [a b & rest], {:key pattern, :default {symbol value}}
+pub fn check_pattern(pattern: &Value, form: &str) -> Result<()> {
+    match pattern {
+        Value::Symbol(sym) if *sym == *SYMBOL_ANPERSAND => Err(invalid_pattern(form, pattern)),
+        Value::Symbol(_) => Ok(()),
+        Value::Vector(_) | Value::List(_) => {
+            let elements = match pattern {
+                Value::Vector(v) => &v.value,
+                Value::List(l) => &l.value,
+                _ => unreachable!(),
+            };
+            for (i, element) in elements.iter().enumerate() {
+                if is_anpersand(element) {
+                    // exactly one pattern after &
+                    if i + 2 != elements.len() {
+                        return Err(Error::Syntax(format!("{}: & must be followed by exactly one pattern", form)));
+                    }
+                    return check_pattern(&elements[i + 1], form);
+                }
+                check_pattern(element, form)?;
+            }
+            Ok(())
+        }
+        Value::Map(m) => {
+            for (key, sub) in m.value.iter() {
+                if is_default_key(key) {
+                    match sub {
+                        Value::Map(defaults) if defaults.value.keys().all(|k| k.is_symbol()) => continue,
+                        _ => return Err(Error::Syntax(format!("{}: :default must be a map of symbols", form))),
+                    }
+                }
+                check_pattern(sub, form)?;
+            }
+            Ok(())
+        }
+        _ => Err(invalid_pattern(form, pattern)),
+    }
+}
+
+// [x #i64 y] => [x y] and the types of the positional parameters (#any if not annotated)
+pub fn split_annotations(params: &Vector, form: &str) -> Result<(Vector, Vec<Type>)> {
+    let len = params.value.iter().position(is_anpersand).unwrap_or(params.value.len());
+    let mut names = vec![];
+    let mut types: Vec<Option<Type>> = vec![];
+    for param in params.value[..len].iter() {
+        match param {
+            Value::Type(t) => match types.last_mut() {
+                Some(last @ None) => *last = Some(t.clone()),
+                _ => return Err(Error::Syntax(format!("{}: type annotation must follow a parameter", form))),
+            },
+            param => {
+                names.push(param.clone());
+                types.push(None);
+            }
+        }
+    }
+
+    if params.value[len..].iter().any(|p| matches!(p, Value::Type(_))) {
+        return Err(Error::Syntax(format!(
+            "{}: type annotations are only allowed on positional parameters",
+            form
+        )));
+    }
+    names.extend(params.value[len..].iter().cloned());
+
+    Ok((
+        Vector::from(names),
+        types.into_iter().map(|t| t.unwrap_or(Type::Any)).collect(),
+    ))
+}
+
+// validate a parameter vector of fn, defn, macro, ...
+// [positional... & rest & {:key pattern, :default {...}}]
+pub fn parse_params(params: &Vector, form: &str) -> Result<Vec<Value>> {
+    let values = &params.value;
+    let len = values.iter().position(is_anpersand).unwrap_or(values.len());
+    for param in values[..len].iter() {
+        check_pattern(param, form)?;
+    }
+
+    let tail = &values[len..];
+    match tail {
+        [] => {}
+        [_, rest] => check_pattern(rest, form)?,
+        [_, rest, amp, keywords @ Value::Map(_)] if !rest.is_map() && is_anpersand(amp) => {
+            check_pattern(rest, form)?;
+            check_pattern(keywords, form)?;
+        }
+        _ => {
+            return Err(Error::Syntax(format!(
+                "{}: parameters must be [positional... & rest & {{keyword arguments}}]",
+                form
+            )))
+        }
+    }
+
+    // keyword arguments are looked up by keyword
+    if let Some(Value::Map(m)) = tail.last() {
+        if m.value.keys().any(|k| !k.is_keyword()) {
+            return Err(Error::Syntax(format!("{}: keyword argument keys must be keywords", form)));
+        }
+    }
+
+    Ok(values.clone())
+}
+
+// bind a value to a pattern: missing elements and keys are bound to nil
+pub fn bind_pattern(pattern: &Value, value: Value, environment: &Rc<RefCell<Environment>>) -> Result<()> {
+    match pattern {
+        Value::Symbol(sym) if sym.name == "_" => Ok(()),
+        Value::Symbol(sym) => environment.borrow_mut().insert(sym, value),
+        Value::Vector(_) | Value::List(_) => {
+            let elements = match pattern {
+                Value::Vector(v) => &v.value,
+                Value::List(l) => &l.value,
+                _ => unreachable!(),
+            };
+            let items = match value {
+                Value::Vector(v) => v.value,
+                Value::List(l) => l.value,
+                Value::Nil => vec![],
+                v => {
+                    return Err(Error::Type(format!(
+                        "cannot destructure {} with sequential pattern {}",
+                        v.type_name(),
+                        pattern
+                    )))
+                }
+            };
+            for (i, element) in elements.iter().enumerate() {
+                if is_anpersand(element) {
+                    let rest = match items.get(i..) {
+                        Some(rest) if !rest.is_empty() => Value::as_vector(rest.to_vec())?,
+                        _ => Value::Nil,
+                    };
+                    return bind_pattern(&elements[i + 1], rest, environment);
+                }
+                bind_pattern(element, items.get(i).cloned().unwrap_or(Value::Nil), environment)?;
+            }
+            Ok(())
+        }
+        Value::Map(m) => {
+            let map = match value {
+                Value::Map(map) => Some(map),
+                Value::Nil => None,
+                v => {
+                    return Err(Error::Type(format!(
+                        "cannot destructure {} with map pattern {}",
+                        v.type_name(),
+                        pattern
+                    )))
+                }
+            };
+            let defaults = m.value.iter().find(|(k, _)| is_default_key(k)).map(|(_, d)| d);
+            for (key, sub) in m.value.iter() {
+                if is_default_key(key) {
+                    continue;
+                }
+                let found = map.as_ref().and_then(|map| map.get(key)).cloned();
+                let val = match (found, defaults) {
+                    (Some(val), _) => val,
+                    (None, Some(Value::Map(d))) => match d.get(sub) {
+                        Some(default) => eval(default.clone(), environment.clone(), false)?,
+                        None => Value::Nil,
+                    },
+                    (None, _) => Value::Nil,
+                };
+                bind_pattern(sub, val, environment)?;
+            }
+            Ok(())
+        }
+        _ => Err(Error::Syntax(format!("invalid binding pattern {}", pattern))),
+    }
+}
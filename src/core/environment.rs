@@ -1,8 +1,9 @@
 /* core/environment.rs */
 
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use nohash::BuildNoHashHasher;
@@ -10,8 +11,17 @@ use nohash::BuildNoHashHasher;
 use crate::core::builtin::constants::*;
 use crate::core::builtin::functions::*;
 use crate::core::builtin::r#macros::*;
+use crate::core::module::Namespace;
+use crate::core::types::condition::{handler_stack, restart_stack, Handler, Restart};
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
+use crate::core::types::error::EXCEPTION_TYPES;
+use crate::core::types::generic::Generic;
+use crate::core::types::meta::Meta;
+use crate::core::types::protocol::Protocol;
+use crate::core::types::r#enum::EnumType;
+use crate::core::types::r#struct::StructType;
+use crate::core::types::r#type::Type;
 use crate::core::types::symbol::Symbol;
 use crate::core::value::Value;
 
@@ -22,6 +32,16 @@ pub struct Environment {
     pub parent: Option<Rc<RefCell<Environment>>>,
     pub current: Rc<RefCell<Lookup>>,
     pub gensym_id: u64,
+    pub handlers: Rc<RefCell<Vec<Handler>>>,
+    pub restarts: Rc<RefCell<Vec<Restart>>>,
+    pub protocols: Rc<RefCell<HashMap<String, Rc<Protocol>>>>,
+    pub generics: Rc<RefCell<HashMap<String, Rc<Generic>>>>,
+    pub namespace: Rc<RefCell<Namespace>>,
+    pub typedefs: Rc<RefCell<HashMap<String, Type>>>, // (typedef #int #i64)
+    pub structs: HashMap<String, Rc<StructType>>,     // (struct Name ...) in this scope
+    pub enums: HashMap<String, Rc<EnumType>>,         // (enum Name ...) in this scope
+    pub annotations: HashMap<String, Type>,           // (def x #i64 ...) in this scope, checked by set!
+    pub dynamics: HashSet<String>,                    // (def-dynamic *x* ...) in this scope, rebound by binding
 }
 
 impl Environment {
@@ -30,11 +50,22 @@ impl Environment {
             parent: None,
             current: Rc::new(RefCell::new(HashMap::default())),
             gensym_id: 0,
+            handlers: handler_stack(),
+            restarts: restart_stack(),
+            protocols: Rc::new(RefCell::new(HashMap::new())),
+            generics: Rc::new(RefCell::new(HashMap::new())),
+            namespace: Rc::new(RefCell::new(Namespace::default())),
+            typedefs: Rc::new(RefCell::new(HashMap::new())),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            annotations: HashMap::new(),
+            dynamics: HashSet::new(),
         }));
 
         insert_builtin_macros(&mut result.borrow_mut());
         insert_builtin_functions(&mut result.borrow_mut());
         insert_builtin_constants(&mut result.borrow_mut());
+        insert_builtin_exceptions(&mut result.borrow_mut());
 
         result
     }
@@ -43,30 +74,47 @@ impl Environment {
         Rc::new(RefCell::new(Self {
             parent: Some(parent.clone()),
             current: Rc::new(RefCell::new(HashMap::default())),
-            gensym_id: parent.borrow_mut().gensym_id,
+            gensym_id: parent.borrow().gensym_id,
+            handlers: parent.borrow().handlers.clone(),
+            restarts: parent.borrow().restarts.clone(),
+            protocols: parent.borrow().protocols.clone(),
+            generics: parent.borrow().generics.clone(),
+            namespace: parent.borrow().namespace.clone(),
+            typedefs: parent.borrow().typedefs.clone(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            annotations: HashMap::new(),
+            dynamics: HashSet::new(),
         }))
     }
 
     pub fn get(&self, key: &Symbol) -> Result<Value> {
-        if let Some(value) = self.current.borrow().get(key) {
-            return Ok(value.clone());
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value(&self, key: &Symbol) -> Result<(Symbol, Value)> {
+        if let Some(found) = self.lookup(key) {
+            return Ok(found);
         }
-        if let Some(parent) = self.parent.clone() {
-            return parent.borrow().get(key);
+
+        // alias/name => exported binding of the imported module
+        if let Some((alias, name)) = key.name.split_once('/') {
+            if let Some(module) = self.namespace.borrow().aliases.get(alias) {
+                return module.get(name)?.ok_or_else(|| Error::Name(key.to_string()));
+            }
         }
 
         Err(Error::Name(key.to_string()))
     }
 
-    pub fn get_key_value(&self, key: &Symbol) -> Result<(Symbol, Value)> {
+    fn lookup(&self, key: &Symbol) -> Option<(Symbol, Value)> {
         if let Some((k, v)) = self.current.borrow().get_key_value(key) {
-            return Ok((k.clone(), v.clone()));
+            return Some((k.clone(), v.clone()));
         }
-        if let Some(parent) = self.parent.clone() {
-            return parent.borrow().get_key_value(key);
+        match &self.parent {
+            Some(parent) => parent.borrow().lookup(key),
+            None => None,
         }
-
-        Err(Error::Name(key.to_string()))
     }
 
     pub fn insert(&mut self, key: &Symbol, value: Value) -> Result<()> {
@@ -93,6 +141,9 @@ impl Environment {
         match self.current.borrow_mut().entry(key.clone()) {
             Entry::Occupied(mut entry) => {
                 if entry.key().meta.mutable {
+                    if let Some(t) = self.annotations.get(key.name.as_ref()) {
+                        t.check(&format!("binding {}", key), &value)?;
+                    }
                     entry.insert(value);
                 } else {
                     return Err(Error::Const(format!("cannot overwrite immutable binding '{}'", key)));
@@ -109,6 +160,97 @@ impl Environment {
         Ok(())
     }
 
+    // (binding [*x* value] ...) replaces the value where *x* is defined by def-dynamic (a local of the same name is skipped),
+    // the old value is returned to be restored
+    pub fn rebind(&mut self, key: &Symbol, value: Value) -> Result<Value> {
+        // alias/*x* => rebound in the environment of the imported module (only exported names)
+        if let Some((alias, name)) = key.name.split_once('/') {
+            let module = self.namespace.borrow().aliases.get(alias).cloned();
+            if let Some(module) = module {
+                if module.get(name)?.is_none() {
+                    return Err(Error::Name(key.to_string()));
+                }
+                return module.environment.borrow_mut().rebind(&runtime_symbol(name), value);
+            }
+        }
+
+        match self.rebind_dynamic(key, value)? {
+            Some(old) => Ok(old),
+            None if self.lookup(key).is_some() => Err(Error::Value(format!(
+                "binding: '{}' is not dynamic, define it with def-dynamic",
+                key
+            ))),
+            None => Err(Error::Name(key.to_string())),
+        }
+    }
+
+    fn rebind_dynamic(&mut self, key: &Symbol, value: Value) -> Result<Option<Value>> {
+        if self.dynamics.contains(key.name.as_ref()) && self.current.borrow().contains_key(key) {
+            if let Some(t) = self.annotations.get(key.name.as_ref()) {
+                t.check(&format!("binding {}", key), &value)?;
+            }
+            return Ok(self.current.borrow_mut().insert(key.clone(), value));
+        }
+        match self.parent.clone() {
+            Some(parent) => parent.borrow_mut().rebind_dynamic(key, value),
+            None => Ok(None),
+        }
+    }
+
+    pub fn annotate(&mut self, key: &Symbol, annotation: Option<Type>) {
+        match annotation {
+            Some(t) => self.annotations.insert(key.name.to_string(), t),
+            None => self.annotations.remove(key.name.as_ref()),
+        };
+    }
+
+    // struct and enum types are scoped like bindings, the innermost definition is found first
+    pub fn get_struct(&self, name: &str) -> Option<Rc<StructType>> {
+        match self.structs.get(name) {
+            Some(struct_type) => Some(struct_type.clone()),
+            None => self.parent.as_ref().and_then(|parent| parent.borrow().get_struct(name)),
+        }
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<Rc<EnumType>> {
+        match self.enums.get(name) {
+            Some(enum_type) => Some(enum_type.clone()),
+            None => self.parent.as_ref().and_then(|parent| parent.borrow().get_enum(name)),
+        }
+    }
+
+    pub fn visible_structs(&self) -> HashMap<String, Rc<StructType>> {
+        let mut result = match &self.parent {
+            Some(parent) => parent.borrow().visible_structs(),
+            None => HashMap::new(),
+        };
+        result.extend(self.structs.iter().map(|(name, t)| (name.clone(), t.clone())));
+        result
+    }
+
+    // #int => #i64 after (typedef #int #i64), any other name must be a struct or an enum
+    pub fn resolve_type(&self, t: &Type) -> Result<Type> {
+        let resolved = t.resolve(&self.typedefs.borrow());
+        let undefined = resolved
+            .user_names()
+            .into_iter()
+            .find(|name| self.get_struct(name).is_none() && self.get_enum(name).is_none());
+        match undefined {
+            Some(name) => Err(Error::Name(format!("#{}", name))),
+            None => Ok(resolved),
+        }
+    }
+
+    // gensym ids are shared by the whole environment tree
+    pub fn next_gensym_id(&mut self) -> u64 {
+        if let Some(parent) = self.parent.clone() {
+            return parent.borrow_mut().next_gensym_id();
+        }
+        let id = self.gensym_id;
+        self.gensym_id += 1;
+        id
+    }
+
     pub fn get_all_symbols(&self) -> Vec<Symbol> {
         let mut result = Vec::<Symbol>::new();
         for (key, _) in self.current.borrow().iter() {
@@ -123,6 +265,8 @@ impl Environment {
 
 fn insert_builtin_functions(env: &mut Environment) {
     let _ = env.insert(&SYMBOL_TYPE, Value::Function(Rc::new(TypeFn)));
+    let _ = env.insert(&SYMBOL_TYPE_OF, Value::Function(Rc::new(TypeOfFn)));
+    let _ = env.insert(&SYMBOL_ISA, Value::Function(Rc::new(IsaFn)));
     let _ = env.insert(&SYMBOL_PRINT, Value::Function(Rc::new(PrintFn)));
     let _ = env.insert(&SYMBOL_INPUT, Value::Function(Rc::new(InputFn)));
     let _ = env.insert(&SYMBOL_INC, Value::Function(Rc::new(IncFn)));
@@ -169,6 +313,23 @@ fn insert_builtin_functions(env: &mut Environment) {
     let _ = env.insert(&SYMBOL_FIRST, Value::Function(Rc::new(FirstFn)));
     let _ = env.insert(&SYMBOL_REST, Value::Function(Rc::new(RestFn)));
     let _ = env.insert(&SYMBOL_RANGE, Value::Function(Rc::new(RangeFn)));
+    let _ = env.insert(&SYMBOL_TAKE, Value::Function(Rc::new(TakeFn)));
+    let _ = env.insert(&SYMBOL_DROP, Value::Function(Rc::new(DropFn)));
+    let _ = env.insert(&SYMBOL_TAKE_WHILE, Value::Function(Rc::new(TakeWhileFn)));
+    let _ = env.insert(&SYMBOL_ITERATE, Value::Function(Rc::new(IterateFn)));
+    let _ = env.insert(&SYMBOL_CYCLE, Value::Function(Rc::new(CycleFn)));
+    let _ = env.insert(&SYMBOL_ZIP, Value::Function(Rc::new(ZipFn)));
+    let _ = env.insert(&SYMBOL_ENUMERATE, Value::Function(Rc::new(EnumerateFn)));
+    let _ = env.insert(&SYMBOL_CHAIN, Value::Function(Rc::new(ChainFn)));
+    let _ = env.insert(&SYMBOL_COLLECT, Value::Function(Rc::new(CollectFn)));
+    let _ = env.insert(&SYMBOL_YIELD, Value::Function(Rc::new(YieldFn)));
+    let _ = env.insert(&SYMBOL_ATOM, Value::Function(Rc::new(AtomFn)));
+    let _ = env.insert(&SYMBOL_DEREF, Value::Function(Rc::new(DerefFn)));
+    let _ = env.insert(&SYMBOL_SWAPE, Value::Function(Rc::new(SwapEFn)));
+    let _ = env.insert(&SYMBOL_RESETE, Value::Function(Rc::new(ResetEFn)));
+    let _ = env.insert(&SYMBOL_COMPARE_AND_SETE, Value::Function(Rc::new(CompareAndSetEFn)));
+    let _ = env.insert(&SYMBOL_ADD_WATCH, Value::Function(Rc::new(AddWatchFn)));
+    let _ = env.insert(&SYMBOL_REMOVE_WATCH, Value::Function(Rc::new(RemoveWatchFn)));
     let _ = env.insert(&SYMBOL_SQRT, Value::Function(Rc::new(SqrtFn)));
     let _ = env.insert(&SYMBOL_ABS, Value::Function(Rc::new(AbsFn)));
     let _ = env.insert(&SYMBOL_COS, Value::Function(Rc::new(CosFn)));
@@ -228,24 +389,53 @@ fn insert_builtin_functions(env: &mut Environment) {
     let _ = env.insert(&SYMBOL_MAP, Value::Function(Rc::new(MapFn)));
     let _ = env.insert(&SYMBOL_FILTER, Value::Function(Rc::new(FilterFn)));
     let _ = env.insert(&SYMBOL_REDUCE, Value::Function(Rc::new(ReduceFn)));
+    let _ = env.insert(&SYMBOL_PARTIAL, Value::Function(Rc::new(PartialFn)));
+    let _ = env.insert(&SYMBOL_COMP, Value::Function(Rc::new(CompFn)));
+    let _ = env.insert(&SYMBOL_JUXT, Value::Function(Rc::new(JuxtFn)));
+    let _ = env.insert(&SYMBOL_COMPLEMENT, Value::Function(Rc::new(ComplementFn)));
+    let _ = env.insert(&SYMBOL_CONSTANTLY, Value::Function(Rc::new(ConstantlyFn)));
+    let _ = env.insert(&SYMBOL_IDENTITY, Value::Function(Rc::new(IdentityFn)));
+    let _ = env.insert(&SYMBOL_FNIL, Value::Function(Rc::new(FnilFn)));
+    let _ = env.insert(&SYMBOL_MEMOIZE, Value::Function(Rc::new(MemoizeFn)));
+}
+
+fn insert_builtin_exceptions(env: &mut Environment) {
+    for name in EXCEPTION_TYPES {
+        let symbol = Symbol {
+            name: Cow::Borrowed(name),
+            meta: Meta {
+                doc: Cow::Owned(format!("Create a {} exception from a message and optional data.", name)),
+                mutable: false,
+                arglists: vec![],
+            },
+            hash: fxhash::hash(name),
+        };
+        let _ = env.insert(&symbol, Value::Function(Rc::new(ExceptionFn { name })));
+    }
 }
 
 fn insert_builtin_macros(env: &mut Environment) {
     let _ = env.insert(&SYMBOL_DEF, Value::Macro(Rc::new(DefMacro)));
     let _ = env.insert(&SYMBOL_CONST, Value::Macro(Rc::new(ConstMacro)));
+    let _ = env.insert(&SYMBOL_DEF_DYNAMIC, Value::Macro(Rc::new(DefDynamicMacro)));
     let _ = env.insert(&SYMBOL_SETE, Value::Macro(Rc::new(SetEMacro)));
     let _ = env.insert(&SYMBOL_LET, Value::Macro(Rc::new(LetMacro)));
+    let _ = env.insert(&SYMBOL_BINDING, Value::Macro(Rc::new(BindingMacro)));
     let _ = env.insert(&SYMBOL_QUOTE, Value::Macro(Rc::new(QuoteMacro)));
     let _ = env.insert(&SYMBOL_SYNTAX_QUOTE, Value::Macro(Rc::new(SyntaxQuoteMacro)));
     let _ = env.insert(&SYMBOL_DO, Value::Macro(Rc::new(DoMacro)));
     let _ = env.insert(&SYMBOL_IF, Value::Macro(Rc::new(IfMacro)));
     let _ = env.insert(&SYMBOL_WHEN, Value::Macro(Rc::new(WhenMacro)));
     let _ = env.insert(&SYMBOL_WHILE, Value::Macro(Rc::new(WhileMacro)));
+    let _ = env.insert(&SYMBOL_LOOP, Value::Macro(Rc::new(LoopMacro)));
+    let _ = env.insert(&SYMBOL_RECUR, Value::Macro(Rc::new(RecurMacro)));
     let _ = env.insert(&SYMBOL_SWITCH, Value::Macro(Rc::new(SwitchMacro)));
+    let _ = env.insert(&SYMBOL_MATCH, Value::Macro(Rc::new(MatchMacro)));
     let _ = env.insert(&SYMBOL_TIME, Value::Macro(Rc::new(TimeMacro)));
     let _ = env.insert(&SYMBOL_DOC, Value::Macro(Rc::new(DocMacro)));
     let _ = env.insert(&SYMBOL_FN, Value::Macro(Rc::new(FnMacro)));
     let _ = env.insert(&SYMBOL_DEFN, Value::Macro(Rc::new(DefnMacro)));
+    let _ = env.insert(&SYMBOL_DEFGEN, Value::Macro(Rc::new(DefgenMacro)));
     let _ = env.insert(&SYMBOL_THREAD_FIRST, Value::Macro(Rc::new(ThreadFirstMacro)));
     let _ = env.insert(&SYMBOL_THREAD_LAST, Value::Macro(Rc::new(ThreadLastMacro)));
     let _ = env.insert(&SYMBOL_COND, Value::Macro(Rc::new(CondMacro)));
@@ -254,9 +444,31 @@ fn insert_builtin_macros(env: &mut Environment) {
     let _ = env.insert(&SYMBOL_FOR, Value::Macro(Rc::new(ForMacro)));
     let _ = env.insert(&SYMBOL_GENSYM, Value::Macro(Rc::new(GensymMacro)));
     let _ = env.insert(&SYMBOL_MACRO, Value::Macro(Rc::new(MacroMacro)));
+    let _ = env.insert(&SYMBOL_STRUCT, Value::Macro(Rc::new(StructMacro)));
+    let _ = env.insert(&SYMBOL_METHOD, Value::Macro(Rc::new(MethodMacro)));
+    let _ = env.insert(&SYMBOL_ANCESTOR, Value::Macro(Rc::new(AncestorMacro)));
+    let _ = env.insert(&SYMBOL_DOT, Value::Macro(Rc::new(DotMacro)));
+    let _ = env.insert(&SYMBOL_ENUM, Value::Macro(Rc::new(EnumMacro)));
+    let _ = env.insert(&SYMBOL_DEFPROTOCOL, Value::Macro(Rc::new(DefprotocolMacro)));
+    let _ = env.insert(&SYMBOL_EXTEND_TYPE, Value::Macro(Rc::new(ExtendTypeMacro)));
+    let _ = env.insert(&SYMBOL_SATISFIES, Value::Macro(Rc::new(SatisfiesMacro)));
+    let _ = env.insert(&SYMBOL_TYPEDEF, Value::Macro(Rc::new(TypedefMacro)));
+    let _ = env.insert(&SYMBOL_SUBTYPE, Value::Macro(Rc::new(SubtypeMacro)));
+    let _ = env.insert(&SYMBOL_IMPORT, Value::Macro(Rc::new(ImportMacro)));
+    let _ = env.insert(&SYMBOL_EXPORT, Value::Macro(Rc::new(ExportMacro)));
+    let _ = env.insert(&SYMBOL_MACROEXPAND_1, Value::Macro(Rc::new(Macroexpand1Macro)));
+    let _ = env.insert(&SYMBOL_MACROEXPAND, Value::Macro(Rc::new(MacroexpandMacro)));
+    let _ = env.insert(&SYMBOL_MACROEXPAND_ALL, Value::Macro(Rc::new(MacroexpandAllMacro)));
     let _ = env.insert(&SYMBOL_INSERTE, Value::Macro(Rc::new(InsertEMacro)));
     let _ = env.insert(&SYMBOL_REMOVEE, Value::Macro(Rc::new(RemoveEMacro)));
     let _ = env.insert(&SYMBOL_REPLACEE, Value::Macro(Rc::new(ReplaceEMacro)));
+    let _ = env.insert(&SYMBOL_TRY, Value::Macro(Rc::new(TryMacro)));
+    let _ = env.insert(&SYMBOL_THROW, Value::Macro(Rc::new(ThrowMacro)));
+    let _ = env.insert(&SYMBOL_SIGNAL, Value::Macro(Rc::new(SignalMacro)));
+    let _ = env.insert(&SYMBOL_HANDLER_BIND, Value::Macro(Rc::new(HandlerBindMacro)));
+    let _ = env.insert(&SYMBOL_RESTART_CASE, Value::Macro(Rc::new(RestartCaseMacro)));
+    let _ = env.insert(&SYMBOL_INVOKE_RESTART, Value::Macro(Rc::new(InvokeRestartMacro)));
+    let _ = env.insert(&SYMBOL_COMPUTE_RESTARTS, Value::Macro(Rc::new(ComputeRestartsMacro)));
 }
 
 fn insert_builtin_constants(env: &mut Environment) {
@@ -1,9 +1,10 @@
 /* core/eval.rs */
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use crate::core::environment::Environment;
+use crate::core::types::condition::signal;
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
 use crate::core::types::function::Function;
@@ -14,6 +15,51 @@ use crate::core::value::Value;
 use super::builtin::macros::SYMBOL_UNQUOTE;
 use super::builtin::macros::SYMBOL_UNQUOTE_SPLICING;
 
+// deep enough for ordinary recursion, shallow enough for the interpreter stack (STACK_SIZE in main.rs)
+pub const DEFAULT_MAX_EVAL_DEPTH: usize = 10000;
+
+thread_local! {
+    static EVAL_DEPTH: Cell<usize> = const { Cell::new(0) };
+    static MAX_EVAL_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_EVAL_DEPTH) };
+}
+
+pub fn set_max_eval_depth(depth: usize) {
+    MAX_EVAL_DEPTH.with(|max| max.set(depth));
+}
+
+// a generator body runs on its own stack (GENERATOR_STACK_SIZE in builtin/generators.rs),
+// starting this many levels below the maximum depth
+pub const GENERATOR_MAX_EVAL_DEPTH: usize = 2000;
+
+pub fn generator_eval_depth() -> usize {
+    MAX_EVAL_DEPTH.with(|max| max.get()).saturating_sub(GENERATOR_MAX_EVAL_DEPTH)
+}
+
+// the depth belongs to the stack being run, switched when a generator is resumed or suspended
+pub fn swap_eval_depth(depth: usize) -> usize {
+    EVAL_DEPTH.with(|d| d.replace(depth))
+}
+
+// counts nested list evaluation, released on drop (also when unwinding with an error)
+struct DepthGuard;
+
+impl DepthGuard {
+    fn enter() -> Result<Self> {
+        let depth = EVAL_DEPTH.with(|d| d.get()) + 1;
+        if depth > MAX_EVAL_DEPTH.with(|max| max.get()) {
+            return Err(Error::RecursionLimit(depth));
+        }
+        EVAL_DEPTH.with(|d| d.set(depth));
+        Ok(DepthGuard)
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        EVAL_DEPTH.with(|d| d.set(d.get() - 1));
+    }
+}
+
 fn splicing_expand(values: Vec<Value>) -> Vec<Value> {
     let mut result = Vec::<Value>::new();
     for value in values {
@@ -38,7 +84,30 @@ fn eval_rest(rest: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<
     Ok(result)
 }
 
-fn eval_list(list: List, environment: Rc<RefCell<Environment>>, syntax_quote: bool) -> Result<Value> {
+fn eval_list(list: List, environment: Rc<RefCell<Environment>>, syntax_quote: bool, tail: bool) -> Result<Value> {
+    match call_list(list, environment.clone(), syntax_quote, tail) {
+        Err(err) => Err(signal_error(err, environment)?),
+        result => result,
+    }
+}
+
+// An error raised by a builtin is signaled like a thrown condition, before the stack unwinds past
+// the restarts around it. It goes on as that condition, so the enclosing lists do not signal it again.
+fn signal_error(err: Error, environment: Rc<RefCell<Environment>>) -> Result<Error> {
+    if matches!(err, Error::Exception(_) | Error::RecursionLimit(_)) || environment.borrow().handlers.borrow().is_empty() {
+        return Ok(err);
+    }
+    let condition = match err.to_exception() {
+        Some(condition) => condition,
+        None => return Ok(err),
+    };
+    signal(&condition, environment)?;
+    Ok(Error::Exception(condition))
+}
+
+fn call_list(list: List, environment: Rc<RefCell<Environment>>, syntax_quote: bool, tail: bool) -> Result<Value> {
+    let _guard = DepthGuard::enter()?;
+
     let mut list_inner = list.value.clone();
     list_inner = splicing_expand(list_inner);
 
@@ -80,6 +149,9 @@ fn eval_list(list: List, environment: Rc<RefCell<Environment>>, syntax_quote: bo
         Value::Function(func) => {
             rest = eval_rest(rest, environment)?;
             rest = splicing_expand(rest);
+            if tail {
+                return Err(Error::TailCall(func, rest, vec![]));
+            }
             func.call(rest)
         }
         Value::I64(int) => {
@@ -102,6 +174,7 @@ fn eval_list(list: List, environment: Rc<RefCell<Environment>>, syntax_quote: bo
             rest = splicing_expand(rest);
             v.call(rest)
         }
+        Value::Macro(mac) if tail => mac.call_tail(rest, environment),
         Value::Macro(mac) => mac.call(rest, environment),
         f => Err(Error::Syntax(format!("cannot call '{}'", f))),
     };
@@ -109,6 +182,26 @@ fn eval_list(list: List, environment: Rc<RefCell<Environment>>, syntax_quote: bo
     result
 }
 
+// a function call in tail position is returned as Error::TailCall instead of growing the stack
+pub fn eval_tail(value: Value, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+    match value {
+        Value::List(list) => eval_list(list, environment, false, true),
+        value => eval(value, environment, false),
+    }
+}
+
+// evaluate forms in order, the last one in tail position
+pub fn eval_body_tail(body: &[Value], environment: Rc<RefCell<Environment>>) -> Result<Value> {
+    let (last, init) = match body.split_last() {
+        Some(split) => split,
+        None => return Ok(Value::Nil),
+    };
+    for form in init {
+        eval(form.clone(), environment.clone(), false)?;
+    }
+    eval_tail(last.clone(), environment)
+}
+
 pub fn eval_ast(ast: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
     let mut result = None;
     for expr in ast {
@@ -130,12 +223,16 @@ pub fn eval(value: Value, environment: Rc<RefCell<Environment>>, syntax_quote: b
         | Value::Macro(_)
         | Value::ControlFlow(_)
         | Value::Generator(_)
+        | Value::Struct(_)
+        | Value::Enum(_)
+        | Value::Atom(_)
         | Value::Splicing(_) => Ok(value),
+        Value::Type(t) => Ok(Value::Type(environment.borrow().resolve_type(&t)?)),
         Value::Slice(s) => {
             let start = eval(s.start.clone(), environment.clone(), syntax_quote)?;
             let end = eval(s.end.clone(), environment.clone(), syntax_quote)?;
             let step = eval(s.step.clone(), environment.clone(), syntax_quote)?;
-            for v in vec![start.clone(), end.clone(), step.clone()] {
+            for v in [start.clone(), end.clone(), step.clone()] {
                 match v {
                     Value::I64(_) | Value::Nil => {}
                     _ => return Err(Error::Type("slice can contain only i64 or nil".to_string())),
@@ -149,7 +246,7 @@ pub fn eval(value: Value, environment: Rc<RefCell<Environment>>, syntax_quote: b
             }
             Ok(environment.borrow().get(&symbol)?)
         }
-        Value::List(list) => eval_list(list, environment, syntax_quote),
+        Value::List(list) => eval_list(list, environment, syntax_quote, false),
         Value::Vector(vector) => {
             let mut result: Vec<Value> = vector
                 .value
@@ -0,0 +1,146 @@
+/* core/module.rs */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::core::builtin::macros::runtime_symbol;
+use crate::core::environment::Environment;
+use crate::core::eval::eval_ast;
+use crate::core::parse::parse;
+use crate::core::read::read;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::symbol::Symbol;
+use crate::core::value::Value;
+
+pub const MODULE_EXTENSION: &str = "sl";
+pub const MODULE_PATH_VAR: &str = "SCILISP_PATH";
+
+// per-file state shared by the environments of a file
+#[derive(Debug, Default, PartialEq)]
+pub struct Namespace {
+    pub dir: Option<PathBuf>,                 // relative imports are resolved from here
+    pub aliases: HashMap<String, Rc<Module>>, // st/mean => aliases["st"]
+    pub exports: Option<Vec<String>>,         // None => everything defined in the file
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl Module {
+    // None if the module does not define the name
+    pub fn get(&self, name: &str) -> Result<Option<(Symbol, Value)>> {
+        let environment = self.environment.borrow();
+        let found = environment
+            .current
+            .borrow()
+            .get_key_value(&runtime_symbol(name))
+            .map(|(k, v)| (k.clone(), v.clone()));
+        let exported = match &environment.namespace.borrow().exports {
+            Some(exports) => exports.iter().any(|e| e == name),
+            None => true,
+        };
+        match found {
+            Some(_) if !exported => Err(Error::Value(format!("{} is not exported from {}", name, self.name))),
+            found => Ok(found),
+        }
+    }
+}
+
+thread_local! {
+    static SEARCH_PATH: RefCell<Vec<PathBuf>> = const { RefCell::new(vec![]) };
+    static MODULES: RefCell<HashMap<PathBuf, Rc<Module>>> = RefCell::new(HashMap::new());
+    static LOADING: RefCell<Vec<PathBuf>> = const { RefCell::new(vec![]) };
+}
+
+pub fn set_search_path(paths: Vec<PathBuf>) {
+    SEARCH_PATH.with(|p| *p.borrow_mut() = paths);
+}
+
+// the directory of the importing file, then --path, then SCILISP_PATH
+fn search_dirs(dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = vec![dir.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."))];
+    SEARCH_PATH.with(|p| dirs.extend(p.borrow().iter().cloned()));
+    if let Some(paths) = env::var_os(MODULE_PATH_VAR) {
+        dirs.extend(env::split_paths(&paths));
+    }
+    dirs
+}
+
+pub fn resolve(name: &str, dir: Option<&Path>) -> Result<PathBuf> {
+    let mut file = PathBuf::from(name);
+    if file.extension().is_none() {
+        file.set_extension(MODULE_EXTENSION);
+    }
+
+    let dirs = search_dirs(dir);
+    let found = if file.is_absolute() {
+        Some(file.clone()).filter(|f| f.is_file())
+    } else {
+        dirs.iter().map(|d| d.join(&file)).find(|f| f.is_file())
+    };
+
+    match found {
+        Some(path) => Ok(path.canonicalize().unwrap_or(path)),
+        None => Err(Error::Value(format!(
+            "module '{}' not found (searched {})",
+            name,
+            dirs.iter()
+                .map(|d| d.display().to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))),
+    }
+}
+
+// the environment of a file: a local environment of a fresh root, so that only its own definitions are exported
+pub fn new_file_environment(dir: Option<PathBuf>) -> Rc<RefCell<Environment>> {
+    let environment = Environment::new_local_environment(Environment::new_root_environment());
+    environment.borrow_mut().namespace = Rc::new(RefCell::new(Namespace {
+        dir,
+        ..Default::default()
+    }));
+    environment
+}
+
+// each module is evaluated once, later imports share it
+pub fn load(path: &Path) -> Result<Rc<Module>> {
+    if let Some(module) = MODULES.with(|m| m.borrow().get(path).cloned()) {
+        return Ok(module);
+    }
+
+    if LOADING.with(|l| l.borrow().iter().any(|p| p == path)) {
+        return Err(Error::Value(format!("circular import of {}", path.display())));
+    }
+
+    LOADING.with(|l| l.borrow_mut().push(path.to_path_buf()));
+    let result = evaluate(path);
+    LOADING.with(|l| l.borrow_mut().retain(|p| p != path));
+
+    let module = Rc::new(result?);
+    MODULES.with(|m| m.borrow_mut().insert(path.to_path_buf(), module.clone()));
+    Ok(module)
+}
+
+fn evaluate(path: &Path) -> Result<Module> {
+    let content = std::fs::read_to_string(path).map_err(Error::IO)?;
+    let parsed = parse(&content)?;
+    let mut ast = Vec::<Value>::new();
+    read(&mut ast, parsed)?;
+
+    let environment = new_file_environment(path.parent().map(Path::to_path_buf));
+    eval_ast(ast, environment.clone())?;
+
+    Ok(Module {
+        name: path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        path: path.to_path_buf(),
+        environment,
+    })
+}
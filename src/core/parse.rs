@@ -11,7 +11,7 @@ use crate::core::types::error::Result;
 #[grammar = "core/pest/grammar.pest"]
 pub struct Parser;
 
-pub fn parse(input: &str) -> Result<Pair<Rule>> {
+pub fn parse(input: &str) -> Result<Pair<'_, Rule>> {
     let result = Parser::parse(Rule::scilisp, input);
     match result {
         Ok(mut pairs) => {
@@ -0,0 +1,185 @@
+/* core/pattern.rs */
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::builtin::macros::{SYMBOL_DOT, SYMBOL_QUOTE};
+use crate::core::environment::Environment;
+use crate::core::eval::eval;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::lambda::is_anpersand;
+use crate::core::value::Value;
+
+fn invalid_pattern(pattern: &Value) -> Error {
+    Error::Syntax(format!("match: invalid pattern {}", pattern))
+}
+
+// Match a value against a pattern, binding symbols into the environment.
+//   literal            nil, true, 1, 1.5, "s", :k, 'sym  (equality)
+//   _ / symbol         wildcard / bind (a repeated symbol must match the same value)
+//   Unit               a unit variant in scope matches itself instead of binding
+//   [a b & rest]       vector or list (exact length without &)
+//   {:key pattern}     map with the keys, or struct with the fields
+//   (pred? pattern)    predicate is truthy, then match the pattern
+//   (Variant p ...)    enum variant with payload, (Unit) for unit variants
+//   Enum.Variant       qualified variant, also (Enum.Variant p ...) and (Enum.Variant? p)
+pub fn match_pattern(pattern: &Value, value: &Value, environment: &Rc<RefCell<Environment>>) -> Result<bool> {
+    match pattern {
+        Value::Nil | Value::Bool(_) | Value::I64(_) | Value::F64(_) | Value::String(_) | Value::Keyword(_) => {
+            Ok(pattern == value)
+        }
+        Value::Symbol(sym) if sym.name == "_" => Ok(true),
+        Value::Symbol(sym) => {
+            // Red is the variant Color.Red, not a catch-all binding
+            if let Ok(Value::Enum(e)) = environment.borrow().get(sym) {
+                if e.payload.is_empty() && e.variant_name() == sym.name {
+                    return Ok(Value::Enum(e) == *value);
+                }
+            }
+            // a repeated symbol matches the value it was bound to earlier in the pattern: [x x]
+            let bound = environment.borrow().current.borrow().get(sym).cloned();
+            if let Some(bound) = bound {
+                return Ok(bound == *value);
+            }
+            environment.borrow_mut().insert(sym, value.clone())?;
+            Ok(true)
+        }
+        Value::Vector(v) => match_sequence(&v.value, value, environment),
+        Value::Map(m) => {
+            for (key, sub) in m.value.iter() {
+                let found = match (value, key) {
+                    (Value::Map(map), key) => map.get(key).cloned(),
+                    (Value::Struct(s), Value::Keyword(_)) => s.borrow().fields.get(key).cloned(),
+                    _ => return Ok(false),
+                };
+                match found {
+                    Some(found) if match_pattern(sub, &found, environment)? => continue,
+                    _ => return Ok(false),
+                }
+            }
+            Ok(true)
+        }
+        Value::List(l) => {
+            let (head, subs) = match l.value.split_first() {
+                // Color.Red
+                Some((Value::Symbol(head), _)) if *head == *SYMBOL_DOT => {
+                    return Ok(eval(pattern.clone(), environment.clone(), false)? == *value);
+                }
+                Some((Value::Symbol(head), subs)) => (head, subs),
+                // (Result.Ok x), (Result.Ok? x)
+                Some((member @ Value::List(_), subs)) if is_member_access(member) => {
+                    let variant = eval(member.clone(), environment.clone(), false)?;
+                    if is_predicate_access(member) {
+                        return match_predicate(&variant, subs, value, pattern, environment);
+                    }
+                    return match_variant(&variant, subs, value, pattern, environment);
+                }
+                _ => return Err(invalid_pattern(pattern)),
+            };
+
+            if *head == *SYMBOL_QUOTE {
+                return match subs {
+                    [quoted] => Ok(quoted == value),
+                    _ => Err(invalid_pattern(pattern)),
+                };
+            }
+
+            // (pred? pattern)
+            if head.name.ends_with('?') {
+                let predicate = environment.borrow().get(head)?;
+                return match_predicate(&predicate, subs, value, pattern, environment);
+            }
+
+            // (Variant pattern ...)
+            let variant = environment.borrow().get(head)?;
+            match_variant(&variant, subs, value, pattern, environment)
+        }
+        _ => Err(invalid_pattern(pattern)),
+    }
+}
+
+fn is_member_access(form: &Value) -> bool {
+    match form {
+        Value::List(l) => matches!(l.value.first(), Some(Value::Symbol(head)) if *head == *SYMBOL_DOT),
+        _ => false,
+    }
+}
+
+// (. Result :Ok?)
+fn is_predicate_access(form: &Value) -> bool {
+    match form {
+        Value::List(l) => matches!(l.value.last(), Some(Value::Keyword(k)) if k.name.ends_with('?')),
+        _ => false,
+    }
+}
+
+fn match_predicate(
+    predicate: &Value,
+    subs: &[Value],
+    value: &Value,
+    pattern: &Value,
+    environment: &Rc<RefCell<Environment>>,
+) -> Result<bool> {
+    let predicate = match (predicate, subs) {
+        (Value::Function(f), [_]) => f,
+        _ => return Err(invalid_pattern(pattern)),
+    };
+    if !predicate.call(vec![value.clone()])?.is_truthy() {
+        return Ok(false);
+    }
+    match_pattern(&subs[0], value, environment)
+}
+
+fn match_variant(
+    variant: &Value,
+    subs: &[Value],
+    value: &Value,
+    pattern: &Value,
+    environment: &Rc<RefCell<Environment>>,
+) -> Result<bool> {
+    match (variant, value) {
+        (Value::Function(f), Value::Enum(e)) if e.is_constructed_by(f) => {
+            if subs.len() != e.payload.len() {
+                return Err(invalid_pattern(pattern));
+            }
+            for (sub, item) in subs.iter().zip(e.payload.iter()) {
+                if !match_pattern(sub, item, environment)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        (Value::Function(_), _) => Ok(false),
+        (unit @ Value::Enum(_), value) if subs.is_empty() => Ok(unit == value),
+        _ => Err(invalid_pattern(pattern)),
+    }
+}
+
+fn match_sequence(patterns: &[Value], value: &Value, environment: &Rc<RefCell<Environment>>) -> Result<bool> {
+    let items = match value {
+        Value::Vector(v) => &v.value,
+        Value::List(l) => &l.value,
+        _ => return Ok(false),
+    };
+
+    let rest_at = patterns.iter().position(is_anpersand);
+    let fixed = rest_at.unwrap_or(patterns.len());
+    if items.len() < fixed || (rest_at.is_none() && items.len() != fixed) {
+        return Ok(false);
+    }
+
+    for (sub, item) in patterns[..fixed].iter().zip(items.iter()) {
+        if !match_pattern(sub, item, environment)? {
+            return Ok(false);
+        }
+    }
+
+    match rest_at {
+        Some(i) => match patterns.get(i + 1..) {
+            Some([rest]) => match_pattern(rest, &Value::as_vector(items[fixed..].to_vec())?, environment),
+            _ => Err(Error::Syntax("match: & must be followed by exactly one pattern".to_string())),
+        },
+        None => Ok(true),
+    }
+}
@@ -4,10 +4,13 @@ use std::rc::Rc;
 
 use pest::iterators::Pair;
 
+use crate::core::builtin::functions::SYMBOL_DEREF;
 use crate::core::builtin::macros::*;
 use crate::core::parse::Rule;
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
+use crate::core::types::keyword::Keyword;
+use crate::core::types::map::Map;
 use crate::core::types::slice::Slice;
 use crate::core::value::Value;
 
@@ -23,11 +26,15 @@ fn read_scilisp(pair: Pair<Rule>) -> Result<Value> {
         Rule::f64 => Value::as_f64(pair),
         Rule::symbol => Value::as_symbol(pair),
         Rule::keyword => Value::as_keyword(pair),
+        Rule::annotation => Value::as_type(pair),
         Rule::regex => Value::as_regex(pair),
         Rule::string => Value::as_string(pair),
-        Rule::list => Value::as_list(inner_collect(pair)?),
+        Rule::member => as_member(pair),
+        Rule::method => Err(Error::Syntax(format!("{} must be at the head of a list", pair.as_str()))),
+        Rule::list => as_list(pair),
         Rule::vector => Value::as_vector(inner_collect(pair)?),
-        Rule::map => Value::as_map({
+        // keys are checked by as_map after evaluation (symbol keys are allowed in patterns)
+        Rule::map => Ok(Value::Map(Map::from({
             let pairs = pair.into_inner().collect::<Vec<_>>();
             let result: Result<Vec<(Value, Value)>> = pairs
                 .chunks(2)
@@ -50,12 +57,13 @@ fn read_scilisp(pair: Pair<Rule>) -> Result<Value> {
                 })
                 .collect();
             result?
-        }),
+        }))),
         Rule::set => Value::as_set(inner_collect(pair)?),
         Rule::quote => quote_to_ast(pair),
         Rule::syntax_quote => syntax_quote_to_ast(pair),
         Rule::unquote => unquote_to_ast(pair),
         Rule::unquote_splicing => unquote_splicing_to_ast(pair),
+        Rule::deref => deref_to_ast(pair),
         Rule::slice => as_slice(pair),
         _ => {
             println!("pair: {:?}", pair.as_str());
@@ -76,6 +84,18 @@ pub fn read(ast: &mut Vec<Value>, pair: Pair<Rule>) -> Result<()> {
     Ok(())
 }
 
+// top-level forms with the line they start on (for diagnostics)
+pub fn read_lines(pair: Pair<Rule>) -> Result<Vec<(usize, Value)>> {
+    let toplevel = pair.into_inner().next().unwrap().into_inner(); // scilisp->scilisp_inner
+    toplevel
+        .filter(|expr| expr.as_rule() != Rule::EOI)
+        .map(|expr| {
+            let line = expr.as_span().start_pos().line_col().0;
+            read_scilisp(expr).map(|value| (line, value))
+        })
+        .collect()
+}
+
 fn quote_to_ast(pair: Pair<Rule>) -> Result<Value> {
     let pair = pair.into_inner().next().unwrap();
     let value = read_scilisp(pair)?;
@@ -100,6 +120,49 @@ fn unquote_splicing_to_ast(pair: Pair<Rule>) -> Result<Value> {
     Value::as_list(vec![Value::Symbol((*SYMBOL_UNQUOTE_SPLICING).clone()), value])
 }
 
+fn deref_to_ast(pair: Pair<Rule>) -> Result<Value> {
+    let pair = pair.into_inner().next().unwrap();
+    let value = read_scilisp(pair)?;
+    Value::as_list(vec![Value::Symbol((*SYMBOL_DEREF).clone()), value])
+}
+
+fn as_dot(target: Value, member: &str) -> Result<Value> {
+    Value::as_list(vec![
+        Value::Symbol((*SYMBOL_DOT).clone()),
+        target,
+        Value::Keyword(Keyword {
+            name: format!(":{}", member),
+        }),
+    ])
+}
+
+// a.b.c => (. (. a :b) :c)
+fn as_member(pair: Pair<Rule>) -> Result<Value> {
+    let mut inner = pair.into_inner();
+    let target = Value::as_symbol(inner.next().unwrap())?;
+    inner.try_fold(target, |target, member| as_dot(target, member.as_str()))
+}
+
+// (.member obj args ...) => (. obj (:member args ...))
+fn as_list(pair: Pair<Rule>) -> Result<Value> {
+    let mut inner = pair.into_inner().peekable();
+    let member = match inner.peek() {
+        Some(first) if first.as_rule() == Rule::method => inner.next().unwrap().into_inner().next().unwrap(),
+        _ => return Value::as_list(inner.map(read_scilisp).collect::<Result<Vec<Value>>>()?),
+    };
+
+    let mut args = inner.map(read_scilisp).collect::<Result<Vec<Value>>>()?;
+    if args.is_empty() {
+        return Err(Error::Syntax(format!(".{} needs a target", member.as_str())));
+    }
+    let target = args.remove(0);
+    let mut call = vec![Value::Keyword(Keyword {
+        name: format!(":{}", member.as_str()),
+    })];
+    call.extend(args);
+    Value::as_list(vec![Value::Symbol((*SYMBOL_DOT).clone()), target, Value::as_list(call)?])
+}
+
 fn as_slice(pair: Pair<Rule>) -> Result<Value> {
     let mut slice_start = Value::Nil;
     let mut slice_end = Value::Nil;
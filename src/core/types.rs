@@ -1,17 +1,25 @@
 /* core/types.rs */
 
+pub mod atom;
+pub mod condition;
+pub mod r#enum;
 pub mod error;
 pub mod function;
 pub mod generator;
+pub mod generic;
 pub mod keyword;
 pub mod lambda;
 pub mod list;
 pub mod r#macro;
 pub mod map;
 pub mod meta;
+pub mod protocol;
 pub mod set;
 pub mod slice;
 pub mod sliceable;
+pub mod r#struct;
 pub mod symbol;
+pub mod r#type;
 pub mod type_name;
+pub mod user_macro;
 pub mod vector;
@@ -0,0 +1,85 @@
+/* core/types/atom.rs */
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::types::error::Result;
+use crate::core::types::function::Function;
+use crate::core::value::Value;
+
+// shared mutable reference cell: (atom 0), @a, (swap! a inc), (reset! a 0)
+#[derive(Debug)]
+pub struct Atom {
+    value: RefCell<Value>,
+    watches: RefCell<Vec<(Value, Rc<dyn Function>)>>, // key and callback, in the order added
+}
+
+impl Atom {
+    pub fn new(value: Value) -> Rc<Self> {
+        Rc::new(Atom {
+            value: RefCell::new(value),
+            watches: RefCell::new(vec![]),
+        })
+    }
+
+    pub fn deref(&self) -> Value {
+        self.value.borrow().clone()
+    }
+
+    pub fn reset(self: &Rc<Self>, value: Value) -> Result<Value> {
+        let old = self.value.replace(value.clone());
+        self.notify(old, value.clone())?;
+        Ok(value)
+    }
+
+    // the current value is not borrowed while f runs, so f may read the atom
+    pub fn swap(self: &Rc<Self>, f: &Rc<dyn Function>, args: Vec<Value>) -> Result<Value> {
+        let mut f_args = vec![self.deref()];
+        f_args.extend(args);
+        let value = f.call(f_args)?;
+        self.reset(value)
+    }
+
+    pub fn compare_and_set(self: &Rc<Self>, old: &Value, new: Value) -> Result<bool> {
+        if *self.value.borrow() != *old {
+            return Ok(false);
+        }
+        self.reset(new)?;
+        Ok(true)
+    }
+
+    // a watch with the same key is replaced
+    pub fn add_watch(&self, key: Value, f: Rc<dyn Function>) {
+        let mut watches = self.watches.borrow_mut();
+        match watches.iter().position(|(k, _)| *k == key) {
+            Some(i) => watches[i].1 = f,
+            None => watches.push((key, f)),
+        }
+    }
+
+    pub fn remove_watch(&self, key: &Value) -> bool {
+        let mut watches = self.watches.borrow_mut();
+        let len = watches.len();
+        watches.retain(|(k, _)| k != key);
+        watches.len() != len
+    }
+
+    // each watch is called with (key atom old new), a watch may add or remove watches
+    fn notify(self: &Rc<Self>, old: Value, new: Value) -> Result<()> {
+        let watches = self.watches.borrow().clone();
+        for (key, f) in watches {
+            f.call(vec![key, Value::Atom(self.clone()), old.clone(), new.clone()])?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value.try_borrow() {
+            Ok(value) => write!(f, "<atom: {:?}>", *value),
+            Err(_) => write!(f, "<atom>"),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+/* core/types/condition.rs */
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::core::environment::Environment;
+use crate::core::types::error::exception_value;
+use crate::core::types::error::type_error;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::keyword::Keyword;
+use crate::core::types::symbol::Symbol;
+use crate::core::value::Value;
+
+// established by handler-bind
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handler {
+    pub types: Vec<Symbol>,
+    pub function: Value,
+}
+
+// established by restart-case
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restart {
+    pub name: Keyword,
+    pub id: u64,
+}
+
+thread_local! {
+    // handlers and restarts are dynamic: the environments of every module share them
+    static HANDLERS: Rc<RefCell<Vec<Handler>>> = Rc::new(RefCell::new(vec![]));
+    static RESTARTS: Rc<RefCell<Vec<Restart>>> = Rc::new(RefCell::new(vec![]));
+    static RESTART_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+pub fn handler_stack() -> Rc<RefCell<Vec<Handler>>> {
+    HANDLERS.with(Rc::clone)
+}
+
+pub fn restart_stack() -> Rc<RefCell<Vec<Restart>>> {
+    RESTARTS.with(Rc::clone)
+}
+
+// unique across modules, so that a restart is never invoked by the id of another one
+pub fn next_restart_id() -> u64 {
+    RESTART_ID.with(|id| {
+        id.set(id.get() + 1);
+        id.get()
+    })
+}
+
+// a condition is an exception map {:type :message :data}
+pub fn to_condition(value: Value) -> Result<Value> {
+    let type_key = Value::Keyword(Keyword {
+        name: ":type".to_string(),
+    });
+
+    match value {
+        Value::String(s) => Ok(exception_value("Exception", s, Value::Nil)),
+        Value::Map(m) => match m.get(&type_key) {
+            Some(Value::Keyword(_)) => Ok(Value::Map(m)),
+            _ => Err(Error::Value("condition map must have a keyword :type".to_string())),
+        },
+        v => Err(type_error("map or string", v.type_name().as_str())),
+    }
+}
+
+pub fn condition_matches(types: &[Symbol], condition: &Value) -> bool {
+    let type_name = match condition {
+        Value::Map(m) => match m.get(&Value::Keyword(Keyword {
+            name: ":type".to_string(),
+        })) {
+            Some(Value::Keyword(k)) => k.name.clone(),
+            _ => return false,
+        },
+        _ => return false,
+    };
+    types
+        .iter()
+        .any(|t| t.name == "Exception" || type_name == format!(":{}", t.name))
+}
+
+// Call matching handlers from the innermost one without unwinding the stack.
+// A handler declines by returning normally, or takes over by invoking a restart (or throwing).
+pub fn signal(condition: &Value, environment: Rc<RefCell<Environment>>) -> Result<()> {
+    let handlers = environment.borrow().handlers.clone();
+    let mut index = handlers.borrow().len();
+
+    while index > 0 {
+        index -= 1;
+        let handler = handlers.borrow()[index].clone();
+        if !condition_matches(&handler.types, condition) {
+            continue;
+        }
+
+        let function = match handler.function {
+            Value::Function(f) => f,
+            v => return Err(type_error("function", v.type_name().as_str())),
+        };
+
+        // the handler runs with only the outer handlers active
+        let inner = handlers.borrow_mut().split_off(index);
+        let result = function.call(vec![condition.clone()]);
+        handlers.borrow_mut().extend(inner);
+
+        result?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+/* core/types/enum.rs */
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::types::error::arity_error;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::function::Function;
+use crate::core::types::keyword::Keyword;
+use crate::core::value::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<Keyword>, // empty for unit variants
+}
+
+#[derive(Debug)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<Variant>,
+    pub constructors: RefCell<Vec<Option<Rc<dyn Function>>>>, // payload variants only
+}
+
+impl EnumType {
+    pub fn new(name: String, variants: Vec<Variant>) -> Rc<Self> {
+        let len = variants.len();
+        Rc::new(EnumType {
+            name,
+            variants,
+            constructors: RefCell::new(vec![None; len]),
+        })
+    }
+
+    // value of a unit variant, or the constructor of a payload variant
+    pub fn variant_value(self: &Rc<Self>, index: usize) -> Value {
+        if self.variants[index].fields.is_empty() {
+            return Value::Enum(Rc::new(EnumValue {
+                enum_type: self.clone(),
+                variant: index,
+                payload: vec![],
+            }));
+        }
+
+        let constructor: Rc<dyn Function> = Rc::new(VariantConstructor {
+            enum_type: self.clone(),
+            variant: index,
+        });
+        self.constructors.borrow_mut()[index] = Some(constructor.clone());
+        Value::Function(constructor)
+    }
+}
+
+impl PartialEq for EnumType {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumValue {
+    pub enum_type: Rc<EnumType>,
+    pub variant: usize,
+    pub payload: Vec<Value>,
+}
+
+impl EnumValue {
+    pub fn variant_name(&self) -> &str {
+        &self.enum_type.variants[self.variant].name
+    }
+
+    pub fn get_field(&self, key: &Keyword) -> Result<Value> {
+        match self.enum_type.variants[self.variant].fields.iter().position(|k| k == key) {
+            Some(i) => Ok(self.payload[i].clone()),
+            None => Err(Error::Key(format!(
+                "{}.{} has no member {}",
+                self.enum_type.name,
+                self.variant_name(),
+                key
+            ))),
+        }
+    }
+
+    // for switch: [Ok] matches any (Ok x)
+    pub fn is_constructed_by(&self, function: &Rc<dyn Function>) -> bool {
+        match &self.enum_type.constructors.borrow()[self.variant] {
+            Some(constructor) => Rc::ptr_eq(constructor, function),
+            None => false,
+        }
+    }
+}
+
+impl PartialEq for EnumValue {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.enum_type, &other.enum_type) && self.variant == other.variant && self.payload == other.payload
+    }
+}
+
+impl fmt::Display for EnumValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.payload.is_empty() {
+            return write!(f, "{}.{}", self.enum_type.name, self.variant_name());
+        }
+        let payload = self.payload.iter().map(|v| format!("{:?}", v)).collect::<Vec<String>>();
+        write!(f, "({}.{} {})", self.enum_type.name, self.variant_name(), payload.join(" "))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantConstructor {
+    pub enum_type: Rc<EnumType>,
+    pub variant: usize,
+}
+
+impl Function for VariantConstructor {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let fields = &self.enum_type.variants[self.variant].fields;
+        if args.len() != fields.len() {
+            return Err(arity_error(fields.len(), args.len()));
+        }
+        Ok(Value::Enum(Rc::new(EnumValue {
+            enum_type: self.enum_type.clone(),
+            variant: self.variant,
+            payload: args,
+        })))
+    }
+}
+
+impl fmt::Display for VariantConstructor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "<variant: {}.{}>",
+            self.enum_type.name, self.enum_type.variants[self.variant].name
+        )
+    }
+}
+
+// (Ok? x)
+#[derive(Debug, Clone)]
+pub struct VariantPredicate {
+    pub enum_type: Rc<EnumType>,
+    pub variant: usize,
+}
+
+impl Function for VariantPredicate {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.len() != 1 {
+            return Err(arity_error(1, args.len()));
+        }
+        let result = match &args[0] {
+            Value::Enum(e) => Rc::ptr_eq(&e.enum_type, &self.enum_type) && e.variant == self.variant,
+            _ => false,
+        };
+        Ok(Value::Bool(result))
+    }
+}
+
+impl fmt::Display for VariantPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "<variant predicate: {}.{}?>",
+            self.enum_type.name, self.enum_type.variants[self.variant].name
+        )
+    }
+}
@@ -2,9 +2,14 @@
 
 use std::fmt::{self, Debug};
 use std::num::{ParseFloatError, ParseIntError};
+use std::rc::Rc;
 use std::str::ParseBoolError;
 
 use crate::core::parse::Rule;
+use crate::core::types::function::Function;
+use crate::core::types::keyword::Keyword;
+use crate::core::types::map::Map;
+use crate::core::types::r#type::Type;
 use crate::core::value::Value;
 
 // use everywhere
@@ -32,8 +37,23 @@ pub enum Error {
     Key(String),
     Value(String),
 
+    // eval nested deeper than the max eval depth
+    RecursionLimit(usize),
+
+    // thrown by user (exception map)
+    Exception(Value),
+
     // return
     Return(Value),
+
+    // invoke-restart (restart id, arguments)
+    InvokeRestart(u64, Vec<Value>),
+
+    // call in tail position (trampolined by Lambda::call) and the return types its value is checked against
+    TailCall(Rc<dyn Function>, Vec<Value>, Vec<Type>),
+
+    // recur (new bindings of loop or fn)
+    Recur(Vec<Value>),
 }
 
 impl fmt::Display for Error {
@@ -63,7 +83,12 @@ impl fmt::Display for Error {
             Index(msg) => write!(f, "Index Error: {}", msg),
             Key(msg) => write!(f, "Key Error: {}", msg),
             Value(msg) => write!(f, "Value Error: {}", msg),
+            RecursionLimit(depth) => write!(f, "Recursion Error: maximum eval depth exceeded ({})", depth),
+            Exception(val) => write!(f, "{}", exception_summary(val)),
             Return(val) => write!(f, "Return: {}", val),
+            InvokeRestart(id, _) => write!(f, "Restart Error: restart {} is not active", id),
+            TailCall(func, _, _) => write!(f, "Tail Call: {}", func),
+            Recur(_) => write!(f, "Syntax Error: recur must be in loop or fn"),
         }
     }
 }
@@ -88,7 +113,12 @@ impl std::error::Error for Error {
             Index(_) => None,
             Key(_) => None,
             Value(_) => None,
+            RecursionLimit(_) => None,
+            Exception(_) => None,
             Return(_) => None,
+            InvokeRestart(_, _) => None,
+            TailCall(_, _, _) => None,
+            Recur(_) => None,
         }
     }
 }
@@ -137,6 +167,77 @@ impl From<regex::Error> for Error {
     }
 }
 
+// exception types which can be caught by try/catch
+pub const EXCEPTION_TYPES: [&str; 14] = [
+    "Exception",
+    "NameError",
+    "TypeError",
+    "SyntaxError",
+    "CastError",
+    "ArityError",
+    "ConstError",
+    "IndexError",
+    "KeyError",
+    "ValueError",
+    "ParseError",
+    "RegexError",
+    "IOError",
+    "RecursionError",
+];
+
+impl Error {
+    // convert to an exception map {:type :message :data}, None for control flow
+    pub fn to_exception(&self) -> Option<Value> {
+        use Error::*;
+        let (name, message) = match self {
+            ParseBool(err) => ("ParseError", err.to_string()),
+            ParseInt(err) => ("ParseError", err.to_string()),
+            ParseFloat(err) => ("ParseError", err.to_string()),
+            PestParse(_) => ("ParseError", self.to_string()),
+            Regex(err) => ("RegexError", err.to_string()),
+            IO(err) => ("IOError", err.to_string()),
+            Readline(err) => ("IOError", err.to_string()),
+            Name(msg) => ("NameError", format!("'{}' is not defined", msg)),
+            Type(msg) => ("TypeError", msg.clone()),
+            Syntax(msg) => ("SyntaxError", msg.clone()),
+            Cast(src, dest) => ("CastError", format!("cannot cast {} to {}", src, dest)),
+            Arity(msg) => ("ArityError", msg.clone()),
+            Const(msg) => ("ConstError", msg.clone()),
+            Index(msg) => ("IndexError", msg.clone()),
+            Key(msg) => ("KeyError", msg.clone()),
+            Value(msg) => ("ValueError", msg.clone()),
+            RecursionLimit(depth) => ("RecursionError", format!("maximum eval depth exceeded ({})", depth)),
+            Exception(val) => return Some(val.clone()),
+            Return(_) | InvokeRestart(_, _) | TailCall(_, _, _) | Recur(_) => return None,
+        };
+        Some(exception_value(name, message, crate::core::value::Value::Nil))
+    }
+}
+
+pub fn exception_value(name: &str, message: String, data: Value) -> Value {
+    Value::Map(Map::from(vec![
+        (keyword(":type"), keyword(format!(":{}", name).as_str())),
+        (keyword(":message"), Value::String(message)),
+        (keyword(":data"), data),
+    ]))
+}
+
+fn keyword(name: &str) -> Value {
+    Value::Keyword(Keyword { name: name.to_string() })
+}
+
+fn exception_summary(exception: &Value) -> String {
+    let field = |name: &str| match exception {
+        Value::Map(m) => m.get(&keyword(name)).cloned().unwrap_or(Value::Nil),
+        _ => Value::Nil,
+    };
+    let name = match field(":type") {
+        Value::Keyword(k) => k.name.trim_start_matches(':').to_string(),
+        _ => "Exception".to_string(),
+    };
+    format!("{}: {}", name, field(":message"))
+}
+
 // error helpers
 pub fn arity_error(expected: usize, actual: usize) -> Error {
     Error::Arity(format!("expected {} arguments, got {}", expected, actual))
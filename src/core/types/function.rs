@@ -9,5 +9,10 @@ use crate::core::value::Value;
 
 pub trait Function: Debug + Display + DynClone {
     fn call(&self, args: Vec<Value>) -> Result<Value>;
+
+    // call with the last body form in tail position (may return Error::TailCall)
+    fn call_tail(&self, args: Vec<Value>) -> Result<Value> {
+        self.call(args)
+    }
 }
 dyn_clone::clone_trait_object!(Function);
@@ -1,8 +1,77 @@
 /* core/types/generator.rs */
 
+use std::cell::RefCell;
 use std::fmt::{Debug, Display};
+use std::rc::Rc;
 
+use crate::core::types::error::type_error;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
 use crate::core::types::sliceable::Sliceable;
 use crate::core::value::Value;
 
-pub trait Generator: Debug + Display + Iterator<Item = Value> + DoubleEndedIterator<Item = Value> + Sliceable {}
+pub trait Generator: Debug + Display + Iterator<Item = Value> + Sliceable {
+    // lazy generators can fail while producing a value (an error of the mapped function, ...)
+    fn try_next(&mut self) -> Result<Option<Value>> {
+        Ok(self.next())
+    }
+
+    // at, len and slice are available (range), otherwise values are read by consuming the generator
+    fn is_sliceable(&self) -> bool {
+        true
+    }
+}
+
+// a running generator (read from its own body) cannot be borrowed, it is read as a lazy one to report the error
+pub fn is_lazy(g: &Rc<RefCell<dyn Generator>>) -> bool {
+    g.try_borrow().map_or(true, |g| !g.is_sliceable())
+}
+
+// reads the values of an iterable one by one
+pub enum Cursor {
+    Indexed(Rc<RefCell<dyn Generator>>, i64), // a sliceable generator is read by index and not moved
+    Consumed(Rc<RefCell<dyn Generator>>),     // a lazy generator is consumed
+    Values(Box<dyn Iterator<Item = Value>>),
+}
+
+impl Cursor {
+    pub fn new(value: Value) -> Result<Cursor> {
+        match value {
+            Value::Generator(g) if !is_lazy(&g) => Ok(Cursor::Indexed(g, 0)),
+            Value::Generator(g) => Ok(Cursor::Consumed(g)),
+            Value::List(_) | Value::Vector(_) | Value::Set(_) | Value::Map(_) => Ok(Cursor::Values(Box::new(value.into_iter()))),
+            Value::String(s) => {
+                let chars = s.chars().map(|c| Value::String(c.to_string())).collect::<Vec<Value>>();
+                Ok(Cursor::Values(Box::new(chars.into_iter())))
+            }
+            _ => Err(type_error(
+                "list, vector, set, map, string or generator",
+                value.type_name().as_str(),
+            )),
+        }
+    }
+
+    pub fn next_value(&mut self) -> Result<Option<Value>> {
+        match self {
+            Cursor::Indexed(g, index) => {
+                let value = g.borrow().at(*index);
+                *index += 1;
+                Ok(value)
+            }
+            Cursor::Consumed(g) => match g.try_borrow_mut() {
+                Ok(mut g) => g.try_next(),
+                Err(_) => Err(Error::Value("generator is already running".to_string())),
+            },
+            Cursor::Values(values) => Ok(values.next()),
+        }
+    }
+
+    // the rest of the values (never returns for an infinite generator)
+    pub fn collect_values(&mut self) -> Result<Vec<Value>> {
+        let mut values = vec![];
+        while let Some(value) = self.next_value()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
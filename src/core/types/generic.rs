@@ -0,0 +1,133 @@
+/* core/types/generic.rs */
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::function::Function;
+use crate::core::types::lambda::{accepts_arity, is_anpersand, typed_arglist, Arity, Lambda};
+use crate::core::value::Value;
+
+// one typed arity of a generic function
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub arity: Arity,
+    pub function: Rc<Lambda>,
+}
+
+impl Method {
+    fn is_variadic(&self) -> bool {
+        self.arity.args.iter().any(is_anpersand)
+    }
+
+    // distance of each argument to its parameter type (None if not applicable)
+    fn distances(&self, args: &[Value]) -> Option<Vec<usize>> {
+        if !accepts_arity(&self.arity.args, args.len()) {
+            return None;
+        }
+        self.arity.types.iter().zip(args).map(|(t, arg)| t.distance(arg)).collect()
+    }
+}
+
+// methods are selected by the runtime types of all arguments (multiple dispatch)
+#[derive(Debug, Clone)]
+pub struct Generic {
+    pub name: String,
+    pub methods: RefCell<Vec<Method>>,
+}
+
+impl Generic {
+    // a method with the same signature is replaced
+    pub fn add_method(&self, method: Method) {
+        let mut methods = self.methods.borrow_mut();
+        let same = methods.iter().position(|m| {
+            m.arity.types == method.arity.types
+                && m.arity.args.len() == method.arity.args.len()
+                && m.is_variadic() == method.is_variadic()
+        });
+        match same {
+            Some(i) => methods[i] = method,
+            None => methods.push(method),
+        }
+    }
+
+    fn select(&self, args: &[Value]) -> Result<Rc<Lambda>> {
+        let methods = self.methods.borrow();
+        let applicable = methods
+            .iter()
+            .filter_map(|m| m.distances(args).map(|d| (m, d)))
+            .collect::<Vec<(&Method, Vec<usize>)>>();
+
+        // at least as specific as every other method in each argument
+        let dominates = |a: &[usize], b: &[usize]| a.iter().zip(b).all(|(x, y)| x <= y);
+        let mut best = applicable
+            .iter()
+            .filter(|(_, d)| applicable.iter().all(|(_, other)| dominates(d, other)))
+            .collect::<Vec<_>>();
+        if best.len() > 1 {
+            best.retain(|(m, _)| !m.is_variadic());
+        }
+
+        // the only method of the arity reports which argument does not match
+        let arity_matches = || {
+            methods
+                .iter()
+                .filter(|m| accepts_arity(&m.arity.args, args.len()))
+                .collect::<Vec<_>>()
+        };
+
+        match best[..] {
+            [(method, _)] => Ok(method.function.clone()),
+            _ if applicable.is_empty() => match arity_matches()[..] {
+                [method] => Ok(method.function.clone()),
+                _ => Err(Error::Type(format!(
+                    "no method of {} matches ({})",
+                    self.name,
+                    arg_types(args)
+                ))),
+            },
+            _ => {
+                let candidates = applicable
+                    .iter()
+                    .map(|(m, _)| typed_arglist(&m.arity))
+                    .collect::<Vec<String>>();
+                Err(Error::Type(format!(
+                    "ambiguous call to {} with ({}): candidates are {}",
+                    self.name,
+                    arg_types(args),
+                    candidates.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+impl PartialEq for Generic {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+fn arg_types(args: &[Value]) -> String {
+    args.iter().map(|a| a.type_name()).collect::<Vec<String>>().join(", ")
+}
+
+impl Function for Generic {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let function = self.select(&args)?;
+        function.call(args)
+    }
+
+    fn call_tail(&self, args: Vec<Value>) -> Result<Value> {
+        let function = self.select(&args)?;
+        function.call_tail(args)
+    }
+}
+
+impl fmt::Display for Generic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<generic function: {}>", self.name)
+    }
+}
@@ -6,6 +6,7 @@ use std::hash::Hash;
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
 use crate::core::types::function::Function;
+use crate::core::types::r#struct::get_member;
 use crate::core::value::Value;
 
 use super::error::{arity_error, key_not_found_error};
@@ -32,6 +33,8 @@ impl Function for Keyword {
                 Some(value) => value.clone(),
                 None => return Err(key_not_found_error(Value::Keyword(self.clone()))),
             },
+            Value::Struct(ref s) => get_member(s, self)?,
+            Value::Enum(ref e) => e.get_field(self)?,
             _ => {
                 return Err(Error::Type(format!("Cannot get {} with {}", args[0].type_name(), self)));
             }
@@ -8,14 +8,17 @@ use std::rc::Rc;
 use once_cell::sync::Lazy;
 
 use crate::core::builtin::macros::{ReturnMacro, SYMBOL_RETURN};
+use crate::core::destructure::bind_pattern;
 use crate::core::environment::Environment;
-use crate::core::eval::eval;
+use crate::core::eval::eval_body_tail;
 use crate::core::types::error::arity_error;
 use crate::core::types::error::arity_error_min;
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
 use crate::core::types::function::Function;
+use crate::core::types::map::Map;
 use crate::core::types::meta::Meta;
+use crate::core::types::r#type::Type;
 use crate::core::types::symbol::Symbol;
 use crate::core::value::Value;
 
@@ -24,68 +27,291 @@ pub static SYMBOL_ANPERSAND: Lazy<Symbol> = Lazy::new(|| Symbol {
     meta: Meta {
         doc: Cow::Borrowed(""),
         mutable: false,
+        arglists: vec![],
     },
     hash: fxhash::hash("&"),
 });
 
 #[derive(Debug, Clone)]
-pub struct Lambda {
-    pub args: Vec<Symbol>,
+pub struct Arity {
+    pub args: Vec<Value>,
+    pub types: Vec<Type>, // positional parameters
+    pub ret: Type,        // [params] => #ret
     pub body: Vec<Value>,
+}
+
+impl Arity {
+    pub fn is_annotated(&self) -> bool {
+        self.types.iter().any(|t| *t != Type::Any)
+    }
+
+    pub fn check_arguments(&self, args: &[Value]) -> Result<()> {
+        for ((t, param), arg) in self.types.iter().zip(&self.args).zip(args) {
+            t.check(&format!("parameter {}", param), arg)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub arities: Vec<Arity>,
     pub environment: Rc<RefCell<Environment>>,
 }
 
-impl Function for Lambda {
-    fn call(&self, args: Vec<Value>) -> Result<Value> {
-        let local_env = Environment::new_local_environment(self.environment.clone());
+pub fn is_anpersand(param: &Value) -> bool {
+    matches!(param, Value::Symbol(sym) if *sym == *SYMBOL_ANPERSAND)
+}
 
-        let argc = self.args.len();
+// [positional... & rest & {:key pattern, :default {...}}] (validated by parse_params)
+struct Params<'a> {
+    positional: &'a [Value],
+    rest: Option<&'a Value>,
+    keywords: Option<&'a Value>,
+}
 
-        let mut exist_rest = false;
+fn split_params(params: &[Value]) -> Params<'_> {
+    let len = params.iter().position(is_anpersand).unwrap_or(params.len());
+    let tail = params[len..].chunks(2).map(|c| &c[1]).collect::<Vec<&Value>>();
 
-        if argc >= 2 {
-            if self.args[argc - 2] == *SYMBOL_ANPERSAND {
-                exist_rest = true;
-            }
-            let rest_symbol = &self.args[argc - 1];
-            local_env.borrow_mut().insert(rest_symbol, Value::Nil)?;
+    let (rest, keywords) = match tail[..] {
+        [] => (None, None),
+        [p] if p.is_map() => (None, Some(p)),
+        [p] => (Some(p), None),
+        [p, k, ..] => (Some(p), Some(k)),
+    };
+
+    Params {
+        positional: &params[..len],
+        rest,
+        keywords,
+    }
+}
+
+pub fn accepts_arity(params: &[Value], argc: usize) -> bool {
+    let params = split_params(params);
+    if params.rest.is_some() || params.keywords.is_some() {
+        argc >= params.positional.len()
+    } else {
+        argc == params.positional.len()
+    }
+}
+
+// trailing keyword arguments: :key value pairs, or a map when the arguments do not end in pairs
+fn split_keyword_args(mut args: Vec<Value>, exist_rest: bool) -> Result<(Vec<Value>, Map)> {
+    let mut start = args.len();
+    while start >= 2 && args[start - 2].is_keyword() {
+        start -= 2;
+    }
+
+    let map = match args.last() {
+        Some(Value::Map(m)) if start == args.len() => {
+            let m = m.clone();
+            args.pop();
+            m
         }
+        _ => {
+            let pairs = args.split_off(start);
+            Map::from(pairs.chunks(2).map(|c| (c[0].clone(), c[1].clone())).collect())
+        }
+    };
+
+    // without a rest parameter there is nowhere to put the remaining arguments
+    if !exist_rest && !args.is_empty() {
+        return Err(Error::Arity(
+            "keyword arguments must be :key value pairs or a map".to_string(),
+        ));
+    }
+
+    Ok((args, map))
+}
+
+fn check_keyword_args(pattern: &Value, keywords: &Map) -> Result<()> {
+    let accepted = match pattern {
+        Value::Map(m) => m
+            .value
+            .keys()
+            .filter(|k| !matches!(k, Value::Keyword(k) if k.name == ":default"))
+            .collect(),
+        _ => vec![],
+    };
+
+    let unknown = keywords
+        .value
+        .keys()
+        .filter(|k| !accepted.contains(k))
+        .map(|k| format!("{:?}", k))
+        .collect::<Vec<String>>();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    Err(Error::Arity(format!(
+        "unknown keyword argument {} (accepts {})",
+        unknown.join(", "),
+        accepted
+            .iter()
+            .map(|k| format!("{:?}", k))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )))
+}
 
-        if exist_rest && args.len() < argc - 2 {
-            return Err(arity_error_min(argc - 2, args.len()));
+// each parameter is a binding pattern (see destructure.rs)
+pub fn bind_arguments(params: &[Value], args: Vec<Value>, environment: &Rc<RefCell<Environment>>) -> Result<()> {
+    let params = split_params(params);
+    let len = params.positional.len();
+
+    if params.rest.is_none() && params.keywords.is_none() {
+        if args.len() != len {
+            return Err(arity_error(len, args.len()));
         }
+    } else if args.len() < len {
+        return Err(arity_error_min(len, args.len()));
+    }
 
-        if !exist_rest && args.len() != self.args.len() {
-            return Err(arity_error(self.args.len(), args.len()));
+    let mut args = args;
+    let extra = args.split_off(len);
+    for (param, arg) in params.positional.iter().zip(args) {
+        bind_pattern(param, arg, environment)?;
+    }
+
+    let rest_args = match params.keywords {
+        Some(pattern) => {
+            let (rest_args, keywords) = split_keyword_args(extra, params.rest.is_some())?;
+            check_keyword_args(pattern, &keywords)?;
+            bind_pattern(pattern, Value::Map(keywords), environment)?;
+            rest_args
         }
+        None => extra,
+    };
 
-        for (i, arg) in args.iter().enumerate() {
-            let sym = &self.args[i];
-            if *sym == *SYMBOL_ANPERSAND {
-                if !exist_rest {
-                    return Err(Error::Type(format!("invalid argument: {}", sym)));
-                }
-                let rest_sym = &self.args[i + 1];
-                let rest_args = Value::as_vector(args[i..].to_vec())?;
-                local_env.borrow_mut().set(rest_sym, rest_args)?;
-                break;
+    if let Some(pattern) = params.rest {
+        let rest_args = if rest_args.is_empty() {
+            Value::Nil
+        } else {
+            Value::as_vector(rest_args)?
+        };
+        bind_pattern(pattern, rest_args, environment)?;
+    }
+
+    Ok(())
+}
+
+// fixed arities take precedence over variadic ones
+pub fn select_arity(arities: &[Arity], argc: usize) -> Option<&Arity> {
+    arities
+        .iter()
+        .find(|a| a.args.len() == argc && !a.args.iter().any(is_anpersand))
+        .or_else(|| arities.iter().find(|a| accepts_arity(&a.args, argc)))
+}
+
+// [x y & more]
+pub fn arglist(params: &[Value]) -> String {
+    let params = params.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+    format!("[{}]", params.join(" "))
+}
+
+// [x #i64 y & more] => #f64
+pub fn typed_arglist(arity: &Arity) -> String {
+    let params = arity
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, p)| match arity.types.get(i) {
+            Some(Type::Any) | None => p.to_string(),
+            Some(t) => format!("{} {}", p, t),
+        })
+        .collect::<Vec<String>>();
+    match arity.ret {
+        Type::Any => format!("[{}]", params.join(" ")),
+        ref ret => format!("[{}] => {}", params.join(" "), ret),
+    }
+}
+
+pub fn arity_mismatch_error(arities: &[Arity], argc: usize) -> Error {
+    let accepted = arities.iter().map(|a| arglist(&a.args)).collect::<Vec<String>>();
+    Error::Arity(format!(
+        "no arity accepts {} arguments (accepts {})",
+        argc,
+        accepted.join(", ")
+    ))
+}
+
+// trampoline: tail calls return to this loop instead of nesting on the stack
+pub fn finish_tail_call(func: Rc<dyn Function>, args: Vec<Value>, rets: Vec<Type>) -> Result<Value> {
+    let mut pending: Vec<Type> = rets;
+    let mut result = func.call_tail(args);
+    while let Err(Error::TailCall(func, args, rets)) = result {
+        for ret in rets {
+            if !pending.contains(&ret) {
+                pending.push(ret);
             }
-            let _ = local_env.borrow_mut().insert(sym, arg.clone());
         }
+        result = func.call_tail(args);
+    }
 
-        local_env
-            .borrow_mut()
-            .insert(&SYMBOL_RETURN, Value::Macro(Rc::new(ReturnMacro)))?;
+    // return types of the annotated functions whose value is the value of a tail call
+    let value = result?;
+    for ret in &pending {
+        ret.check("return value", &value)?;
+    }
+    Ok(value)
+}
 
-        let mut result = Ok(Value::Nil);
-        for val in &self.body {
-            result = eval(val.clone(), local_env.clone(), false);
-            if let Err(Error::Return(v)) = result {
-                return Ok(v);
-            }
+impl Function for Lambda {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        match self.call_tail(args) {
+            Err(Error::TailCall(func, args, rets)) => finish_tail_call(func, args, rets),
+            result => result,
         }
+    }
 
-        result
+    fn call_tail(&self, args: Vec<Value>) -> Result<Value> {
+        let mut args = args;
+        loop {
+            let arity = match &self.arities[..] {
+                // a single arity reports the detailed argument error
+                [arity] => arity,
+                arities => match select_arity(arities, args.len()) {
+                    Some(arity) => arity,
+                    None => return Err(arity_mismatch_error(arities, args.len())),
+                },
+            };
+
+            if arity.is_annotated() {
+                arity.check_arguments(&args)?;
+            }
+
+            let local_env = Environment::new_local_environment(self.environment.clone());
+
+            bind_arguments(&arity.args, args, &local_env)?;
+
+            local_env
+                .borrow_mut()
+                .insert(&SYMBOL_RETURN, Value::Macro(Rc::new(ReturnMacro)))?;
+
+            let result = match eval_body_tail(&arity.body, local_env) {
+                Err(Error::Return(v)) => Ok(v),
+                Err(Error::Recur(recur_args)) => {
+                    args = recur_args;
+                    continue;
+                }
+                result => result,
+            };
+
+            // a tail call has to finish before its value can be checked, the trampoline checks it
+            return match (&arity.ret, result) {
+                (Type::Any, result) => result,
+                (ret, Err(Error::TailCall(func, args, mut rets))) => {
+                    if !rets.contains(ret) {
+                        rets.push(ret.clone());
+                    }
+                    Err(Error::TailCall(func, args, rets))
+                }
+                (ret, result) => result.and_then(|v| ret.check("return value", &v).map(|_| v)),
+            };
+        }
     }
 }
 
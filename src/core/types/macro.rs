@@ -12,5 +12,15 @@ use crate::core::value::Value;
 
 pub trait Macro: Debug + Display + DynClone {
     fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value>;
+
+    // rewrite the form one step without evaluating it (None: not a rewriting macro)
+    fn expand(&self, _args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
+        Ok(None)
+    }
+
+    // evaluate the form in tail position of a function body (see eval_tail)
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        self.call(args, environment)
+    }
 }
 dyn_clone::clone_trait_object!(Macro);
@@ -4,6 +4,7 @@ use std::borrow::Cow;
 pub struct Meta {
     pub doc: Cow<'static, str>,
     pub mutable: bool,
+    pub arglists: Vec<String>,
 }
 
 impl Default for Meta {
@@ -11,6 +12,7 @@ impl Default for Meta {
         Meta {
             doc: Cow::Borrowed("No documentation"),
             mutable: true,
+            arglists: vec![],
         }
     }
 }
@@ -0,0 +1,90 @@
+/* core/types/protocol.rs */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::types::error::arity_error_min;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::function::Function;
+use crate::core::value::Value;
+
+// method name => function
+pub type MethodTable = HashMap<String, Rc<dyn Function>>;
+
+#[derive(Debug)]
+pub struct Protocol {
+    pub name: String,
+    pub methods: Vec<String>,
+    pub impls: RefCell<HashMap<String, MethodTable>>, // by type name
+}
+
+impl Protocol {
+    pub fn has_method(&self, name: &str) -> bool {
+        self.methods.iter().any(|m| m == name)
+    }
+
+    pub fn implement(&self, type_name: String, method: String, function: Rc<dyn Function>) {
+        self.impls.borrow_mut().entry(type_name).or_default().insert(method, function);
+    }
+
+    // the type itself first, then the ancestors of a struct
+    fn dispatch_types(value: &Value) -> Vec<String> {
+        let mut types = vec![value.type_name()];
+        if let Value::Struct(s) = value {
+            types.extend(s.borrow().struct_type.ancestors().iter().map(|t| t.name.clone()));
+        }
+        types
+    }
+
+    pub fn find(&self, method: &str, value: &Value) -> Option<Rc<dyn Function>> {
+        let impls = self.impls.borrow();
+        Protocol::dispatch_types(value)
+            .iter()
+            .find_map(|t| impls.get(t).and_then(|methods| methods.get(method)).cloned())
+    }
+
+    pub fn is_satisfied_by(&self, value: &Value) -> bool {
+        let impls = self.impls.borrow();
+        Protocol::dispatch_types(value).iter().any(|t| impls.contains_key(t))
+    }
+}
+
+impl PartialEq for Protocol {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+// dispatch on the type of the first argument
+#[derive(Debug, Clone)]
+pub struct ProtocolMethod {
+    pub protocol: Rc<Protocol>,
+    pub name: String,
+}
+
+impl Function for ProtocolMethod {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        if args.is_empty() {
+            return Err(arity_error_min(1, args.len()));
+        }
+
+        match self.protocol.find(&self.name, &args[0]) {
+            Some(function) => function.call(args),
+            None => Err(Error::Type(format!(
+                "{} does not implement {}/{}",
+                args[0].type_name(),
+                self.protocol.name,
+                self.name
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ProtocolMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<protocol method: {}/{}>", self.protocol.name, self.name)
+    }
+}
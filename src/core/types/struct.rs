@@ -0,0 +1,185 @@
+/* core/types/struct.rs */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::types::error::arity_error_range;
+use crate::core::types::error::type_error;
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::function::Function;
+use crate::core::types::keyword::Keyword;
+use crate::core::types::map::Map;
+use crate::core::value::Value;
+
+#[derive(Debug)]
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<Keyword>, // inherited fields first
+    pub parent: Option<Rc<StructType>>,
+    pub methods: RefCell<HashMap<String, Value>>,
+    pub constructor: RefCell<Option<Value>>,
+}
+
+impl StructType {
+    pub fn has_field(&self, key: &Keyword) -> bool {
+        self.fields.contains(key)
+    }
+
+    // own methods first, then the parent chain
+    pub fn find_method(&self, name: &str) -> Option<Value> {
+        if let Some(method) = self.methods.borrow().get(name) {
+            return Some(method.clone());
+        }
+        match &self.parent {
+            Some(parent) => parent.find_method(name),
+            None => None,
+        }
+    }
+
+    // nearest first
+    pub fn ancestors(&self) -> Vec<Rc<StructType>> {
+        let mut result = vec![];
+        let mut current = self.parent.clone();
+        while let Some(parent) = current {
+            current = parent.parent.clone();
+            result.push(parent);
+        }
+        result
+    }
+}
+
+impl PartialEq for StructType {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructInstance {
+    pub struct_type: Rc<StructType>,
+    pub fields: Map,
+}
+
+impl StructInstance {
+    pub fn new(struct_type: Rc<StructType>) -> Self {
+        let fields = Map::from(
+            struct_type
+                .fields
+                .iter()
+                .map(|k| (Value::Keyword(k.clone()), Value::Nil))
+                .collect(),
+        );
+        StructInstance { struct_type, fields }
+    }
+
+    pub fn set_field(&mut self, key: &Keyword, value: Value) -> Result<()> {
+        if !self.struct_type.has_field(key) {
+            return Err(no_member_error(&self.struct_type, key));
+        }
+        self.fields.insert(Value::Keyword(key.clone()), value);
+        Ok(())
+    }
+}
+
+impl PartialEq for StructInstance {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.struct_type, &other.struct_type) && self.fields == other.fields
+    }
+}
+
+impl fmt::Display for StructInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}{}", self.struct_type.name, self.fields)
+    }
+}
+
+fn no_member_error(struct_type: &StructType, key: &Keyword) -> Error {
+    Error::Key(format!("{} has no member {}", struct_type.name, key))
+}
+
+// field value, or a method bound to the instance
+pub fn get_member(instance: &Rc<RefCell<StructInstance>>, key: &Keyword) -> Result<Value> {
+    let struct_type = instance.borrow().struct_type.clone();
+    if let Some(value) = instance.borrow().fields.get(&Value::Keyword(key.clone())) {
+        return Ok(value.clone());
+    }
+
+    let name = key.name.trim_start_matches(':');
+    match struct_type.find_method(name) {
+        Some(Value::Function(method)) => Ok(Value::Function(Rc::new(BoundMethod {
+            name: format!("{}.{}", struct_type.name, name),
+            receiver: Value::Struct(instance.clone()),
+            method,
+        }))),
+        _ => Err(no_member_error(&struct_type, key)),
+    }
+}
+
+// calling a struct creates an instance: (Name {:field value}) or (Name args...) with a constructor
+#[derive(Debug, Clone)]
+pub struct StructConstructor {
+    pub struct_type: Rc<StructType>,
+}
+
+impl Function for StructConstructor {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let instance = Rc::new(RefCell::new(StructInstance::new(self.struct_type.clone())));
+
+        let constructor = self.struct_type.constructor.borrow().clone();
+        if let Some(Value::Function(constructor)) = constructor {
+            let mut ctor_args = vec![Value::Struct(instance.clone())];
+            ctor_args.extend(args);
+            constructor.call(ctor_args)?;
+            return Ok(Value::Struct(instance));
+        }
+
+        match args.len() {
+            0 => {}
+            1 => {
+                let init = match &args[0] {
+                    Value::Map(m) => m,
+                    v => return Err(type_error("map", v.type_name().as_str())),
+                };
+                for (key, value) in init.value.iter() {
+                    match key {
+                        Value::Keyword(k) => instance.borrow_mut().set_field(k, value.clone())?,
+                        k => return Err(type_error("keyword", k.type_name().as_str())),
+                    }
+                }
+            }
+            n => return Err(arity_error_range(0, 1, n)),
+        }
+
+        Ok(Value::Struct(instance))
+    }
+}
+
+impl fmt::Display for StructConstructor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<struct: {}>", self.struct_type.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BoundMethod {
+    pub name: String,
+    pub receiver: Value,
+    pub method: Rc<dyn Function>,
+}
+
+impl Function for BoundMethod {
+    fn call(&self, args: Vec<Value>) -> Result<Value> {
+        let mut method_args = vec![self.receiver.clone()];
+        method_args.extend(args);
+        self.method.call(method_args)
+    }
+}
+
+impl fmt::Display for BoundMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<method: {}>", self.name)
+    }
+}
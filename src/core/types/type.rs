@@ -0,0 +1,327 @@
+/* core/types/type.rs */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::r#struct::StructType;
+use crate::core::types::type_name::TypeName;
+use crate::core::value::Value;
+
+// type annotation (#i64, #number, #Enemy, #v[#f64], #fn[#i64] => #i64, ...)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Any,
+    Nil,
+    Bool,
+    I64,
+    F64,
+    Str,
+    Regex,
+    Key,
+    Sym,
+    List,
+    Vector,
+    Map,
+    Set,
+    Fn,
+    Macro,
+    Generator,
+    Slice,
+    Atom,
+    Kind, // type of types (#type)
+    // abstract types
+    Number,
+    Collection,
+    Iterable,
+    // struct or enum
+    User(String),
+    // parameterized types
+    Of(Box<Type>, Box<Type>),        // #v[#i64]: collection and its elements (values of a map)
+    Option(Box<Type>),               // #option[#i64]: nil or i64
+    Signature(Vec<Type>, Box<Type>), // #fn[#i64 #i64] => #i64
+    Union(Vec<Type>),                // (union #i64 #str) or #union[#i64 #str]: one of the types
+}
+
+impl Type {
+    // name without #
+    pub fn from_name(name: &str) -> Type {
+        match name {
+            "any" => Type::Any,
+            "nil" => Type::Nil,
+            "bool" => Type::Bool,
+            "i64" => Type::I64,
+            "f64" => Type::F64,
+            "str" | "string" => Type::Str,
+            "regex" => Type::Regex,
+            "key" | "keyword" => Type::Key,
+            "sym" | "symbol" => Type::Sym,
+            "l" | "list" => Type::List,
+            "v" | "vector" => Type::Vector,
+            "m" | "map" => Type::Map,
+            "s" | "set" => Type::Set,
+            "fn" | "function" => Type::Fn,
+            "macro" => Type::Macro,
+            "generator" => Type::Generator,
+            "slice" => Type::Slice,
+            "atom" => Type::Atom,
+            "type" => Type::Kind,
+            "number" => Type::Number,
+            "collection" => Type::Collection,
+            "iterable" => Type::Iterable,
+            name => Type::User(name.to_string()),
+        }
+    }
+
+    // #name[params] => ret
+    pub fn with_params(name: &str, params: Vec<Type>, ret: Option<Type>) -> Result<Type> {
+        let invalid = |params: &[Type]| {
+            let params = params.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+            Error::Syntax(format!("invalid type #{}[{}]", name, params.join(" ")))
+        };
+
+        match (Type::from_name(name), ret) {
+            (Type::Fn, ret) => Ok(Type::Signature(params, Box::new(ret.unwrap_or(Type::Any)))),
+            (_, Some(_)) => Err(Error::Syntax(format!("only #fn can have a return type, not #{}", name))),
+            (Type::User(option), None) if option == "option" => match &params[..] {
+                [t] => Ok(Type::Option(Box::new(t.clone()))),
+                params => Err(invalid(params)),
+            },
+            (Type::User(union), None) if union == "union" && !params.is_empty() => Ok(Type::union(params)),
+            (
+                base @ (Type::List | Type::Vector | Type::Map | Type::Set | Type::Collection | Type::Iterable | Type::Generator),
+                None,
+            ) => match &params[..] {
+                [t] => Ok(Type::Of(Box::new(base), Box::new(t.clone()))),
+                params => Err(invalid(params)),
+            },
+            _ => Err(invalid(&params)),
+        }
+    }
+
+    // nested unions are flattened, duplicates removed and a single type is not wrapped
+    pub fn union(types: Vec<Type>) -> Type {
+        let mut members: Vec<Type> = vec![];
+        for t in types {
+            let flattened = match t {
+                Type::Union(ts) => ts,
+                t => vec![t],
+            };
+            for t in flattened {
+                if !members.contains(&t) {
+                    members.push(t);
+                }
+            }
+        }
+        match &members[..] {
+            [t] => t.clone(),
+            _ if members.contains(&Type::Any) => Type::Any,
+            _ => Type::Union(members),
+        }
+    }
+
+    // the names of structs and enums in this type
+    pub fn user_names(&self) -> Vec<&str> {
+        match self {
+            Type::User(name) => vec![name],
+            Type::Of(base, element) => [base.user_names(), element.user_names()].concat(),
+            Type::Option(t) => t.user_names(),
+            Type::Signature(params, ret) => params.iter().chain([ret.as_ref()]).flat_map(|t| t.user_names()).collect(),
+            Type::Union(types) => types.iter().flat_map(|t| t.user_names()).collect(),
+            _ => vec![],
+        }
+    }
+
+    // replace the names defined by typedef with their types
+    pub fn resolve(&self, aliases: &HashMap<String, Type>) -> Type {
+        let resolve_all = |types: &[Type]| types.iter().map(|t| t.resolve(aliases)).collect::<Vec<Type>>();
+        match self {
+            Type::User(name) => aliases.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Type::Of(base, element) => Type::Of(Box::new(base.resolve(aliases)), Box::new(element.resolve(aliases))),
+            Type::Option(t) => Type::Option(Box::new(t.resolve(aliases))),
+            Type::Signature(params, ret) => Type::Signature(resolve_all(params), Box::new(ret.resolve(aliases))),
+            Type::Union(types) => Type::union(resolve_all(types)),
+            t => t.clone(),
+        }
+    }
+
+    // this type and its supertypes, nearest first
+    pub fn supertypes(&self) -> Vec<Type> {
+        let mut types = vec![self.clone()];
+        types.extend(match self {
+            Type::I64 | Type::F64 => vec![Type::Number],
+            Type::Str | Type::Generator | Type::Collection => vec![Type::Iterable],
+            Type::List | Type::Vector | Type::Map | Type::Set => vec![Type::Collection, Type::Iterable],
+            _ => vec![],
+        });
+        if *self != Type::Any {
+            types.push(Type::Any);
+        }
+        types
+    }
+
+    // the type of the value and its supertypes, nearest first
+    pub fn lineage(value: &Value) -> Vec<Type> {
+        match value {
+            Value::Struct(s) => {
+                let struct_type = s.borrow().struct_type.clone();
+                let mut types = vec![Type::User(struct_type.name.clone())];
+                types.extend(struct_type.ancestors().iter().map(|t| Type::User(t.name.clone())));
+                types.push(Type::Any);
+                types
+            }
+            Value::ControlFlow(_) | Value::Splicing(_) => vec![Type::Any],
+            value => Type::from(value.type_of()).supertypes(),
+        }
+    }
+
+    // (subtype? #i64 #number), the ancestors of structs are looked up by name
+    pub fn is_subtype(&self, other: &Type, structs: &HashMap<String, Rc<StructType>>) -> bool {
+        let subtype = |a: &Type, b: &Type| a.is_subtype(b, structs);
+        match (self, other) {
+            (_, Type::Any) => true,
+            (a, b) if a == b => true,
+            (Type::Union(types), b) => types.iter().all(|t| subtype(t, b)),
+            (Type::Option(t), b) => subtype(&Type::Nil, b) && subtype(t, b),
+            (a, Type::Union(types)) => types.iter().any(|t| subtype(a, t)),
+            (a, Type::Option(t)) => *a == Type::Nil || subtype(a, t),
+            (Type::Of(ab, ae), Type::Of(bb, be)) => subtype(ab, bb) && subtype(ae, be),
+            (Type::Of(base, _), b) => subtype(base, b),
+            (a, Type::Of(base, element)) => **element == Type::Any && subtype(a, base),
+            // parameters are contravariant, the return value is covariant
+            (Type::Signature(ap, ar), Type::Signature(bp, br)) => {
+                ap.len() == bp.len() && bp.iter().zip(ap).all(|(b, a)| subtype(b, a)) && subtype(ar, br)
+            }
+            (Type::Signature(_, _), b) => subtype(&Type::Fn, b),
+            (Type::User(name), b) => structs
+                .get(name)
+                .is_some_and(|s| s.ancestors().iter().any(|a| Type::User(a.name.clone()) == *b)),
+            (a, b) => a.supertypes().contains(b),
+        }
+    }
+
+    // steps from the type of the value up to this type (None if the value is not of this type)
+    pub fn distance(&self, value: &Value) -> Option<usize> {
+        match self {
+            Type::Of(base, element) => {
+                let distance = base.distance(value)?;
+                mismatched_element(value, element).is_none().then_some(distance)
+            }
+            // one step above both nil and the type
+            Type::Option(t) => match value {
+                Value::Nil => Some(1),
+                value => t.distance(value).map(|d| d + 1),
+            },
+            // arguments and return value of a function are not known until it is called
+            Type::Signature(_, _) => Type::Fn.distance(value),
+            // one step above the nearest member
+            Type::Union(types) => types.iter().filter_map(|t| t.distance(value)).min().map(|d| d + 1),
+            t => Type::lineage(value).iter().position(|l| l == t),
+        }
+    }
+
+    pub fn matches(&self, value: &Value) -> bool {
+        self.distance(value).is_some()
+    }
+
+    // Type Error: parameter x expected #i64, got string
+    pub fn check(&self, what: &str, value: &Value) -> Result<()> {
+        if *self == Type::Any || self.matches(value) {
+            return Ok(());
+        }
+        let actual = match self {
+            Type::Of(base, element) if base.matches(value) => match mismatched_element(value, element) {
+                Some(e) => format!("{} containing {}", value.type_name(), e.type_name()),
+                None => value.type_name(),
+            },
+            _ => value.type_name(),
+        };
+        Err(Error::Type(format!("{} expected {}, got {}", what, self, actual)))
+    }
+}
+
+impl From<TypeName> for Type {
+    fn from(name: TypeName) -> Self {
+        match name {
+            TypeName::Nil => Type::Nil,
+            TypeName::Bool => Type::Bool,
+            TypeName::I64 => Type::I64,
+            TypeName::F64 => Type::F64,
+            TypeName::Symbol => Type::Sym,
+            TypeName::Keyword => Type::Key,
+            TypeName::Regex => Type::Regex,
+            TypeName::String => Type::Str,
+            TypeName::List => Type::List,
+            TypeName::Vector => Type::Vector,
+            TypeName::Map => Type::Map,
+            TypeName::Set => Type::Set,
+            TypeName::Function => Type::Fn,
+            TypeName::Macro => Type::Macro,
+            TypeName::Generator => Type::Generator,
+            TypeName::Slice => Type::Slice,
+            TypeName::Atom => Type::Atom,
+            TypeName::Type => Type::Kind,
+            TypeName::Struct(name) | TypeName::Enum(name) => Type::User(name),
+        }
+    }
+}
+
+// elements checked by #l[...], #v[...], #iterable[...] (a generator is not consumed)
+fn mismatched_element(value: &Value, element: &Type) -> Option<Value> {
+    let mismatch = |e: &&Value| !element.matches(e);
+    match value {
+        Value::List(l) => l.value.iter().find(mismatch).cloned(),
+        Value::Vector(v) => v.value.iter().find(mismatch).cloned(),
+        Value::Set(s) => s.value.iter().find(mismatch).cloned(),
+        Value::Map(m) => m.value.values().find(mismatch).cloned(),
+        Value::String(s) => s.chars().map(|c| Value::String(c.to_string())).find(|e| !element.matches(e)),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::Any => "any",
+            Type::Nil => "nil",
+            Type::Bool => "bool",
+            Type::I64 => "i64",
+            Type::F64 => "f64",
+            Type::Str => "str",
+            Type::Regex => "regex",
+            Type::Key => "key",
+            Type::Sym => "sym",
+            Type::List => "l",
+            Type::Vector => "v",
+            Type::Map => "m",
+            Type::Set => "s",
+            Type::Fn => "fn",
+            Type::Macro => "macro",
+            Type::Generator => "generator",
+            Type::Slice => "slice",
+            Type::Atom => "atom",
+            Type::Kind => "type",
+            Type::Number => "number",
+            Type::Collection => "collection",
+            Type::Iterable => "iterable",
+            Type::User(name) => name,
+            Type::Of(base, element) => return write!(f, "{}[{}]", base, element),
+            Type::Option(t) => return write!(f, "#option[{}]", t),
+            Type::Signature(params, ret) => {
+                let params = params.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+                write!(f, "#fn[{}]", params.join(" "))?;
+                return match **ret {
+                    Type::Any => Ok(()),
+                    ref ret => write!(f, " => {}", ret),
+                };
+            }
+            Type::Union(types) => {
+                let types = types.iter().map(|t| t.to_string()).collect::<Vec<String>>();
+                return write!(f, "#union[{}]", types.join(" "));
+            }
+        };
+        write!(f, "#{}", name)
+    }
+}
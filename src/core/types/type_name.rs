@@ -20,12 +20,23 @@ pub enum TypeName {
     Macro,
     Generator,
     Slice,
+    Atom,
+    Type,
+    Struct(std::string::String),
+    Enum(std::string::String),
 }
 
 use TypeName::*;
+
+// the names of builtin types, as returned by (type x)
+pub const BUILTIN_TYPE_NAMES: [TypeName; 18] = [
+    Nil, Bool, I64, F64, Symbol, Keyword, Regex, String, List, Vector, Map, Set, Function, Macro, Generator, Slice, Atom, Type,
+];
+
 impl fmt::Display for TypeName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let str = match self {
+            Struct(name) | Enum(name) => return write!(f, "{}", name),
             Nil => "nil",
             Bool => "bool",
             I64 => "i64",
@@ -42,6 +53,8 @@ impl fmt::Display for TypeName {
             Macro => "macro",
             Generator => "generator",
             Slice => "slice",
+            Atom => "atom",
+            Type => "type",
         };
         write!(f, "{}", str)
     }
@@ -0,0 +1,71 @@
+/* core/types/user_macro.rs */
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::environment::Environment;
+use crate::core::eval::{eval, eval_tail};
+use crate::core::types::error::Error;
+use crate::core::types::error::Result;
+use crate::core::types::lambda::{arglist, bind_arguments, select_arity, Arity};
+use crate::core::types::r#macro::Macro;
+use crate::core::types::symbol::Symbol;
+use crate::core::value::Value;
+
+#[derive(Debug, Clone)]
+pub struct UserMacro {
+    pub name: Symbol,
+    pub arities: Vec<Arity>,
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl UserMacro {
+    // build the expansion form without evaluating it
+    pub fn expand_form(&self, args: Vec<Value>) -> Result<Value> {
+        let arity = match select_arity(&self.arities, args.len()) {
+            Some(arity) => arity,
+            None => {
+                let accepted = self.arities.iter().map(|a| arglist(&a.args)).collect::<Vec<String>>();
+                return Err(Error::Arity(format!(
+                    "macro '{}' does not accept {} arguments (accepts {})",
+                    self.name,
+                    args.len(),
+                    accepted.join(", ")
+                )));
+            }
+        };
+
+        let local_env = Environment::new_local_environment(self.environment.clone());
+        bind_arguments(&arity.args, args, &local_env)?;
+
+        let mut result = Value::Nil;
+        for val in &arity.body {
+            result = eval(val.clone(), local_env.clone(), false)?;
+        }
+
+        Ok(result)
+    }
+}
+
+impl Macro for UserMacro {
+    fn call(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let expanded = self.expand_form(args)?;
+        eval(expanded, environment, false)
+    }
+
+    fn expand(&self, args: Vec<Value>, _environment: Rc<RefCell<Environment>>) -> Result<Option<Value>> {
+        Ok(Some(self.expand_form(args)?))
+    }
+
+    fn call_tail(&self, args: Vec<Value>, environment: Rc<RefCell<Environment>>) -> Result<Value> {
+        let expanded = self.expand_form(args)?;
+        eval_tail(expanded, environment)
+    }
+}
+
+impl fmt::Display for UserMacro {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<macro: {}>", self.name)
+    }
+}
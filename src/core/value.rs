@@ -10,15 +10,19 @@ use std::rc::Rc;
 use pest::iterators::Pair;
 
 use crate::core::parse::Rule;
+use crate::core::types::atom::Atom;
 use crate::core::types::error::Error;
 use crate::core::types::error::Result;
 use crate::core::types::error::{arity_error, index_out_of_range_error, key_not_found_error};
 use crate::core::types::function::Function;
-use crate::core::types::generator::Generator;
+use crate::core::types::generator::{is_lazy, Cursor, Generator};
 use crate::core::types::keyword::Keyword;
 use crate::core::types::list::List;
 use crate::core::types::map::Map;
+use crate::core::types::r#enum::EnumValue;
 use crate::core::types::r#macro::Macro;
+use crate::core::types::r#struct::StructInstance;
+use crate::core::types::r#type;
 use crate::core::types::set::Set;
 use crate::core::types::slice::Slice;
 use crate::core::types::sliceable::Sliceable;
@@ -45,6 +49,10 @@ pub enum Value {
     Macro(Rc<dyn Macro>),
     Generator(Rc<RefCell<dyn Generator>>),
     Slice(Rc<Slice>),
+    Struct(Rc<RefCell<StructInstance>>),
+    Enum(Rc<EnumValue>),
+    Atom(Rc<Atom>),
+    Type(r#type::Type),
     ControlFlow(Rc<ControlFlow<Value, Value>>),
     Splicing(Vec<Value>),
 }
@@ -65,6 +73,10 @@ impl PartialEq for Value {
             (Map(h1), Map(h2)) => h1 == h2,
             (Set(s1), Set(s2)) => s1 == s2,
             (Slice(s1), Slice(s2)) => s1 == s2,
+            (Struct(s1), Struct(s2)) => Rc::ptr_eq(s1, s2) || *s1.borrow() == *s2.borrow(),
+            (Enum(e1), Enum(e2)) => e1 == e2,
+            (Atom(a1), Atom(a2)) => Rc::ptr_eq(a1, a2),
+            (Type(t1), Type(t2)) => t1 == t2,
             _ => false,
         }
     }
@@ -101,8 +113,15 @@ impl fmt::Display for Value {
             Set(s) => write!(f, "{}", s),
             Function(func) => write!(f, "{}", func),
             Macro(mac) => write!(f, "{}", mac),
-            Generator(g) => write!(f, "{}", g.borrow()),
+            Generator(g) => match g.try_borrow() {
+                Ok(g) => write!(f, "{}", g),
+                Err(_) => write!(f, "<generator: running>"),
+            },
             Slice(s) => write!(f, "{}", s),
+            Struct(s) => write!(f, "{}", s.borrow()),
+            Enum(e) => write!(f, "{}", e),
+            Atom(a) => write!(f, "{}", a),
+            Type(t) => write!(f, "{}", t),
             ControlFlow(cf) => write!(f, "{:?}", cf),
             Splicing(_) => write!(f, "splicing"),
         }
@@ -127,8 +146,15 @@ impl fmt::Debug for Value {
             Set(s) => write!(f, "{}", s),
             Function(func) => write!(f, "{}", func),
             Macro(mac) => write!(f, "{}", mac),
-            Generator(g) => write!(f, "{}", g.borrow()),
+            Generator(g) => match g.try_borrow() {
+                Ok(g) => write!(f, "{}", g),
+                Err(_) => write!(f, "<generator: running>"),
+            },
             Slice(s) => write!(f, "{}", s),
+            Struct(s) => write!(f, "{}", s.borrow()),
+            Enum(e) => write!(f, "{}", e),
+            Atom(a) => write!(f, "{}", a),
+            Type(t) => write!(f, "{}", t),
             ControlFlow(cf) => write!(f, "{:?}", cf),
             Splicing(_) => write!(f, "splicing"),
         }
@@ -137,7 +163,11 @@ impl fmt::Debug for Value {
 
 impl Value {
     pub fn type_name(&self) -> std::string::String {
-        let result = match self {
+        self.type_of().to_string()
+    }
+
+    pub fn type_of(&self) -> TypeName {
+        match self {
             Value::Nil => TypeName::Nil,
             Value::Bool(_) => TypeName::Bool,
             Value::I64(_) => TypeName::I64,
@@ -154,9 +184,12 @@ impl Value {
             Value::Macro(_) => TypeName::Macro,
             Value::Generator(_) => TypeName::Generator,
             Value::Slice(_) => TypeName::Slice,
-            _ => panic!("Cannot get type name of {}", self.type_name()), // OK
-        };
-        result.to_string()
+            Value::Struct(s) => TypeName::Struct(s.borrow().struct_type.name.clone()),
+            Value::Enum(e) => TypeName::Enum(e.enum_type.name.clone()),
+            Value::Atom(_) => TypeName::Atom,
+            Value::Type(_) => TypeName::Type,
+            _ => panic!("Cannot get type name of {}", self), // OK
+        }
     }
 }
 
@@ -205,6 +238,36 @@ impl Value {
         Ok(Value::Keyword(Keyword { name: result }))
     }
 
+    pub fn as_type(pair: Pair<Rule>) -> Result<Value> {
+        let mut inner = pair.into_inner();
+        let name = inner.next().unwrap().as_str();
+
+        let mut params = None;
+        let mut ret = None;
+        for p in inner {
+            match (p.as_rule(), Value::as_types(p)?) {
+                (Rule::type_params, types) => params = Some(types),
+                (Rule::return_type, mut types) => ret = types.pop(),
+                _ => unreachable!(),
+            }
+        }
+
+        let t = match (params, ret) {
+            (None, None) => r#type::Type::from_name(name),
+            (params, ret) => r#type::Type::with_params(name, params.unwrap_or_default(), ret)?,
+        };
+        Ok(Value::Type(t))
+    }
+
+    fn as_types(pair: Pair<Rule>) -> Result<Vec<r#type::Type>> {
+        pair.into_inner()
+            .map(|p| match Value::as_type(p)? {
+                Value::Type(t) => Ok(t),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
     pub fn as_regex(pair: Pair<Rule>) -> Result<Value> {
         let result = pair.into_inner().next().unwrap().as_str();
         let regex = regex::Regex::new(result);
@@ -590,6 +653,20 @@ impl Function for i64 {
                 Some(value) => value,
                 None => return Err(index_out_of_range_error(*self)),
             },
+            // a lazy generator is advanced to the index
+            Value::Generator(ref g) if is_lazy(g) => {
+                if *self < 0 {
+                    return Err(Error::Type(format!("Cannot index {} from the end", args[0])));
+                }
+                let mut cursor = Cursor::new(args[0].clone())?;
+                for _ in 0..*self {
+                    cursor.next_value()?;
+                }
+                match cursor.next_value()? {
+                    Some(value) => value,
+                    None => return Err(index_out_of_range_error(*self)),
+                }
+            }
             Value::Generator(ref g) => match g.borrow().at(*self) {
                 Some(value) => return Ok(value.clone()), // TODO: mutable referenece
                 None => return Err(index_out_of_range_error(*self)),
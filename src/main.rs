@@ -2,44 +2,110 @@
 
 // scilisp  # launch REPL
 // scilisp xxx.sl  # run as script
+// scilisp check --types xxx.sl  # check without running
 
 use std::path::PathBuf;
 use std::process::exit;
+use std::thread;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 mod core;
+use crate::core::cli::check::check;
 use crate::core::cli::repl::{execute, repl};
+use crate::core::eval::{set_max_eval_depth, DEFAULT_MAX_EVAL_DEPTH};
+use crate::core::module::set_search_path;
 
 #[derive(Parser)]
-#[command(version, about, after_help = "If no arguments are provided, it launches a REPL.")]
+#[command(
+    version,
+    about,
+    after_help = "If no arguments are provided, it launches a REPL.",
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(help = "Execute <FILE>")]
     file: Option<PathBuf>,
+
+    #[arg(long, default_value_t = DEFAULT_MAX_EVAL_DEPTH, help = "Maximum eval depth before a RecursionError")]
+    max_depth: usize,
+
+    #[arg(long = "path", value_name = "DIR", help = "Add a module search path (also SCILISP_PATH)")]
+    path: Vec<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Check <FILE> without running it")]
+    Check {
+        #[arg(help = "File to check")]
+        file: PathBuf,
+
+        #[arg(long, help = "Report type errors found by static type inference")]
+        types: bool,
+    },
 }
 
 #[derive(Debug)]
 enum Action {
     Repl,
     Execute(Option<PathBuf>),
+    Check(PathBuf, bool),
 }
 
-fn main() {
-    let args: Args = Args::parse();
+// eval recursion runs on this stack (see DEFAULT_MAX_EVAL_DEPTH), grown with --max-depth
+// (a debug build uses about 12KB per eval depth, a release build about 3KB)
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+const STACK_SIZE_PER_DEPTH: usize = 16 * 1024;
+
+fn stack_size(max_depth: usize) -> usize {
+    STACK_SIZE.max(max_depth.saturating_mul(STACK_SIZE_PER_DEPTH))
+}
 
-    let action = if args.file.is_some() {
-        Action::Execute(args.file)
-    } else {
-        Action::Repl
+fn run(args: Args) -> i32 {
+    set_max_eval_depth(args.max_depth);
+    set_search_path(args.path);
+
+    let action = match (args.command, args.file) {
+        (Some(Command::Check { file, types }), _) => Action::Check(file, types),
+        (None, Some(file)) => Action::Execute(Some(file)),
+        (None, None) => Action::Repl,
     };
 
     let result = match action {
         Action::Repl => repl(),
         Action::Execute(file) => execute(file),
+        Action::Check(file, types) => check(file, types),
     };
 
     if let Err(err) = result {
         eprintln!("{}", err);
-        exit(1);
+        return 1;
     }
+    0
+}
+
+fn main() {
+    let args: Args = Args::parse();
+
+    let max_depth = args.max_depth;
+    let stack_size = stack_size(max_depth);
+
+    let code = match thread::Builder::new().stack_size(stack_size).spawn(move || run(args)) {
+        Ok(handle) => handle.join().unwrap_or(1),
+        Err(err) => {
+            eprintln!(
+                "cannot allocate a {} MB stack for --max-depth {}: {}",
+                stack_size / (1024 * 1024),
+                max_depth,
+                err
+            );
+            1
+        }
+    };
+
+    exit(code);
 }
@@ -1118,3 +1118,1476 @@ fn execute_repl_00069() -> Result<(), Box<dyn std::error::Error>> {
     cmd.assert().success().stdout(format!("{}\n", out));
     Ok(())
 }
+
+#[test]
+fn execute_repl_00070() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (macro my-and
+          "Evaluates exprs one at time,
+           from left to right."
+          ([] true)
+          ([x] x)
+          ([x & next]
+            `(let [and ~x]
+               (if and
+                 (my-and ~@next)
+                 and))))
+        (my-and "a" "b" "c")
+        (my-and)
+        (my-and 1 nil 3)
+        (macro unless [c & body] `(if ~c nil (do ~@body)))
+        (unless false 1 2)
+        (unless true 1 2)
+        "##,
+    );
+    let outs = ["my-and", "\"c\"", "true", "nil", "unless", "2", "nil"];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00071() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (macro unless [c & body] `(if ~c nil (do ~@body)))
+        (macroexpand-1 '(unless false 1 2))
+        (macroexpand '(-> 1 (+ 2) inc))
+        (macroexpand '(->> [1 2] (map inc) (filter even?)))
+        (macroexpand-1 '(cond (< 1 2) "a" :else "b"))
+        (macroexpand-all '(when true (unless false '(unless x))))
+        (macroexpand '(+ 1 2))
+        (-> '(1 2) first)
+        "##,
+    );
+    let outs = [
+        "unless",
+        "(if false nil (do 1 2))",
+        "(inc (+ 1 2))",
+        "(filter even? (map inc [1, 2]))",
+        "(if (< 1 2) \"a\" (cond :else \"b\"))",
+        "(if true (do (if false nil (do (quote (unless x))))))",
+        "(+ 1 2)",
+        "1",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00072() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (macro my-and
+          ([] true)
+          ([x] x)
+          ([x & next]
+            `(let [and# ~x]
+               (if and#
+                 (my-and ~@next)
+                 and#))))
+        (my-and "a" "b" "c")
+        (macroexpand-1 '(my-and 1 2))
+        (macro swap [a b] `(let [tmp# ~a] (set! ~a ~b) (set! ~b tmp#)))
+        (def tmp 1)
+        (def y 2)
+        (swap tmp y)
+        [tmp y]
+        "##,
+    );
+    let outs = [
+        "my-and",
+        "\"c\"",
+        "(let [and__2__auto, 1] (if and__2__auto (my-and 2) and__2__auto))",
+        "swap",
+        "tmp",
+        "y",
+        "y",
+        "[2, 1]",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00073() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (try
+          (print "before error")
+          (throw (TypeError "error"))
+          (print "after error")
+          (catch TypeError e
+            (print e))
+          (finally (print "every time executed")))
+        (try (+ 1 "a") (catch TypeError e (:message e)))
+        (try (undefined-x) (catch [KeyError NameError] e (:type e)))
+        (try (throw {:type :ParseFailure, :message "bad row", :data {:row 3}}) (catch ParseFailure e (:row (:data e))))
+        (try (throw "plain") (catch Exception e e))
+        (defn f [] (try (return 5) (catch Exception e 0)) 9)
+        (f)
+        (def n 0)
+        (try (set! n 1) (finally (set! n (+ n 10))))
+        n
+        "##,
+    );
+    let outs = [
+        "before error",
+        "{:type :TypeError, :message \"error\", :data nil}",
+        "every time executed",
+        "nil",
+        "\"expected type: 'i64 or f64', got: 'string'\"",
+        ":NameError",
+        "3",
+        "{:type :Exception, :message \"plain\", :data nil}",
+        "f",
+        "5",
+        "n",
+        "n",
+        "11",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00074() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (try (throw (KeyError "x")) (catch TypeError e 1))
+        (throw (ValueError "uncaught" [1 2]))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("KeyError: x"))
+        .stderr(predicate::str::contains("ValueError: uncaught"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00075() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn parse-row [row]
+          (restart-case
+            (if (= row "bad") (throw (ValueError "bad row" row)) (i64 row))
+            (:use-value [v] v)
+            (:skip-row [] nil)))
+        (handler-bind [ValueError (fn [e] (invoke-restart :use-value 0))] [(parse-row "1") (parse-row "bad") (parse-row "3")])
+        (handler-bind [ValueError (fn [e] (invoke-restart :skip-row))] (parse-row "bad"))
+        (handler-bind [[KeyError ValueError] (fn [e] (print "logged" (:message e)))]
+          (try (throw (ValueError "x")) (catch ValueError e (:message e))))
+        (restart-case (compute-restarts) (:a [] 1) (:b [] 2))
+        (restart-case (invoke-restart :retry 1 2) (:retry [a b] (+ a b)))
+        (signal "nobody listens")
+        "##,
+    );
+    let outs = ["parse-row", "[1, 0, 3]", "nil", "logged x", "\"x\"", "[:a, :b]", "3", "nil"];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00076() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (let [[a, _, c] [1, 2, 3]] [a c])
+        (let [[a, [b1, b2], c] [1, [2, 3], 4]] [b1 b2])
+        (let [[a & b] [1 2 3]] [a b])
+        (let [[a & b] []] [a b])
+        (let [{:a a, :b b} {:a 1 :b 2}] [a b])
+        (let [{0 a, 1 b} {1 2}] [a b])
+        (let [{:a x, :b y, :default {x 2}} {:b 20}] [x y])
+        (defn norm2 [[x y z]] (+ (* x x) (* y y) (* z z)))
+        (norm2 [1 2 3])
+        ((fn [{:ok ok, :value v}] (if ok v 0)) {:ok true :value 5})
+        (for [[x y] [[1 2] [3 4]]] (print (+ x y)))
+        (defn f [a & [b c]] [a b c])
+        (f 1 2)
+        "##,
+    );
+    let outs = [
+        "[1, 3]",
+        "[2, 3]",
+        "[1, [2, 3]]",
+        "[nil, nil]",
+        "[1, 2]",
+        "[nil, 2]",
+        "[2, 20]",
+        "norm2",
+        "14",
+        "5",
+        "3",
+        "7",
+        "nil",
+        "f",
+        "[1, 2, nil]",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00077() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (let [[a b] 1] a)
+        (let [{:a a} [1]] a)
+        (let [[a &] [1]] a)
+        (fn [1] 1)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "cannot destructure i64 with sequential pattern [a, b]",
+        ))
+        .stderr(predicate::str::contains("cannot destructure vector with map pattern {:a a}"))
+        .stderr(predicate::str::contains("let: & must be followed by exactly one pattern"))
+        .stderr(predicate::str::contains("fn: invalid binding pattern 1"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00078() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn filter-signal [x & {:order order, :cutoff cutoff, :default {order 2, cutoff 0.5}}] [x order cutoff])
+        (filter-signal 1)
+        (filter-signal 1 :order 4 :cutoff 0.2)
+        (filter-signal 1 {:cutoff 0.1})
+        (defn nd [nation & rest & {:year year, :tz tz, :default {year 1970, tz "UTC"}}] [nation rest year tz])
+        (nd "Japan" 2018 1 1 {:tz "UTC+09:00"})
+        (nd "UK" {:year 2018})
+        (nd "FR" 1 2 :tz "CET")
+        "##,
+    );
+    let outs = [
+        "filter-signal",
+        "[1, 2, 0.5]",
+        "[1, 4, 0.2]",
+        "[1, 2, 0.1]",
+        "nd",
+        "[\"Japan\", [2018, 1, 1], 1970, \"UTC+09:00\"]",
+        "[\"UK\", nil, 2018, \"UTC\"]",
+        "[\"FR\", [1, 2], 1970, \"CET\"]",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00079() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn filter-signal [x & {:order order, :cutoff cutoff}] [x order cutoff])
+        (filter-signal 1 :ordr 4 :cutof 0.2)
+        (filter-signal 1 2)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "unknown keyword argument :ordr, :cutof (accepts :order, :cutoff)",
+        ))
+        .stderr(predicate::str::contains(
+            "keyword arguments must be :key value pairs or a map",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00080() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn f "sum things" ([x] x) ([x y] (+ x y)) ([x y & more] (+ x y (len more))))
+        (f 1)
+        (f 1 2)
+        (f 1 2 3 4)
+        (doc f)
+        (defn g "twice" [x] (* x 2))
+        (g 4)
+        ((fn ([] 0) ([a] a)) 7)
+        "##,
+    );
+    let outs = [
+        "f",
+        "1",
+        "3",
+        "5",
+        "------------------------------",
+        "function: f",
+        "([x] [x y] [x y & more])",
+        "sum things",
+        "------------------------------",
+        "nil",
+        "g",
+        "8",
+        "7",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00081() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn f ([x] x) ([x y & more] x))
+        (f)
+        (macro m ([a] a) ([a b] b))
+        (m 1 2 3)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "no arity accepts 0 arguments (accepts [x], [x y & more])",
+        ))
+        .stderr(predicate::str::contains(
+            "macro 'm' does not accept 3 arguments (accepts [a], [a b])",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00082() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn count-down [n] (if (= n 0) :done (count-down (- n 1))))
+        (count-down 20000)
+        (defn even2? [n] (if (= n 0) true (odd2? (- n 1))))
+        (defn odd2? [n] (if (= n 0) false (even2? (- n 1))))
+        (even2? 20001)
+        (loop [i 0, acc 0] (if (< i 20000) (recur (+ i 1) (+ acc i)) acc))
+        (defn sum-to [n acc] (cond (= n 0) acc :else (recur (- n 1) (+ acc n))))
+        (sum-to 20000 0)
+        (loop [[x & xs] [1 2 3] acc []] (if x (recur xs (push acc (* x 10))) acc))
+        (defn fact [n] (if (<= n 1) 1 (* n (fact (- n 1)))))
+        (fact 20)
+        "##,
+    );
+    let outs = [
+        "count-down",
+        ":done",
+        "even2?",
+        "odd2?",
+        "false",
+        "199990000",
+        "sum-to",
+        "200010000",
+        "[10, 20, 30]",
+        "fact",
+        "2432902008176640000",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00083() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (def x 42)
+        (defn d [n] (if (= n 0) 0 (+ 1 (d (- n 1)))))
+        (d 100000)
+        x
+        (d 10)
+        (try (d 100000) (catch RecursionError e (:type e)))
+        "##,
+    );
+    let outs = ["x", "d", "42", "10", ":RecursionError"];
+    let out = outs.join("\n");
+    cmd.assert()
+        .success()
+        .stdout(format!("{}\n", out))
+        .stderr(predicate::str::contains(
+            "Recursion Error: maximum eval depth exceeded (10001)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00084() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.arg("--max-depth").arg("50");
+    cmd.write_stdin(
+        r##"
+        (defn d [n] (if (= n 0) 0 (+ 1 (d (- n 1)))))
+        (d 10)
+        (d 100)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("d\n10\n")
+        .stderr(predicate::str::contains("Recursion Error: maximum eval depth exceeded (51)"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00085() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (struct Enemy "an enemy" [name hp] (defn damage [self n] (set! (:hp self) (- (:hp self) n)) self))
+        (def slime (Enemy {:name "slime" :hp 30}))
+        (:hp slime)
+        ((:damage slime) 10)
+        (struct Boss => Enemy [power]
+          (defn Boss [self name power] (set! (:name self) name) (set! (:hp self) 100) (set! (:power self) power)))
+        (def boss (Boss "dragon" 9))
+        ((:damage boss) 1)
+        (method [Enemy] (defn heal [self] (set! (:hp self) 999)))
+        ((:heal boss))
+        (:hp boss)
+        (ancestor Boss)
+        (ancestor boss)
+        (= (Enemy {:hp 1}) (Enemy {:hp 1}))
+        (Enemy)
+        "##,
+    );
+    let outs = [
+        "Enemy",
+        "slime",
+        "30",
+        "#Enemy{:name \"slime\", :hp 20}",
+        "Boss",
+        "boss",
+        "#Boss{:name \"dragon\", :hp 99, :power 9}",
+        "nil",
+        ":hp",
+        "999",
+        "[Enemy]",
+        "[Enemy]",
+        "true",
+        "#Enemy{:name nil, :hp nil}",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00086() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (struct Point [x y])
+        (Point {:z 1})
+        (:z (Point))
+        (set! (:z (Point)) 1)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("Point\n")
+        .stderr(predicate::str::contains("Key Error: Point has no member :z"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00087() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (enum Color "Color enum" [Red Green Blue])
+        Red
+        (:Green Color)
+        [(= Red Red) (= Red Green) (Red? Red) (Red? 1)]
+        (type Red)
+        (enum Result [(Ok value) (Err msg)])
+        (def r (Ok 42))
+        r
+        [(:value r) (Ok? r) (Err? r) (= (Ok 1) (Ok 1))]
+        (defn show [x] (switch x [Ok] (:value x) [Err] (:msg x) [Red Green] "color" :default "other"))
+        [(show r) (show (Err "bad")) (show Green) (show Blue)]
+        Ok
+        "##,
+    );
+    let outs = [
+        "Color",
+        "Color.Red",
+        "Color.Green",
+        "[true, false, true, false]",
+        "\"Color\"",
+        "Result",
+        "r",
+        "(Result.Ok 42)",
+        "[42, true, false, true]",
+        "show",
+        "[42, \"bad\", \"color\", \"other\"]",
+        "<variant: Result.Ok>",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00088() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (enum Shape [(Circle r) (Rect w h)])
+        (Rect 1)
+        (:r (Rect 1 2))
+        (enum Bad [A A])
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("Shape\n")
+        .stderr(predicate::str::contains("Arity Error: expected 2 arguments, got 1"))
+        .stderr(predicate::str::contains("Key Error: Shape.Rect has no member :r"))
+        .stderr(predicate::str::contains("enum: duplicate variant A"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00089() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (enum Result [(Ok value) (Err msg)])
+        (defn f [x] (match x
+          nil "nil"
+          0 "zero"
+          (i64? n) :when (< n 0) "negative"
+          (i64? n) (+ n 100)
+          "hi" "greeting"
+          'sym "symbol"
+          [a b] [:pair a b]
+          [a & rest] [:head a rest]
+          {:type :point :x x :y y} (+ x y)
+          (Ok [a b]) (* a b)
+          (Ok v) v
+          (Err m) [:error m]
+          _ "other"))
+        [(f nil) (f 0) (f -5) (f 5) (f "hi") (f 'sym)]
+        [(f [1 2]) (f [1 2 3]) (f '(9)) (f {:type :point :x 1 :y 2}) (f {:type :line})]
+        [(f (Ok [3 4])) (f (Ok 7)) (f (Err "bad")) (f 1.5)]
+        (defn cnt [n acc] (match n 0 acc _ (cnt (- n 1) (+ acc 1))))
+        (cnt 20000 0)
+        "##,
+    );
+    let outs = [
+        "Result",
+        "f",
+        "[\"nil\", \"zero\", \"negative\", 105, \"greeting\", \"symbol\"]",
+        "[[:pair, 1, 2], [:head, 1, [2, 3]], [:head, 9, []], 3, \"other\"]",
+        "[12, 7, [:error, \"bad\"], \"other\"]",
+        "cnt",
+        "20000",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00090() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (match "x" 1 :one)
+        (match 3 (i64? n))
+        (try (match [1] [a b] a) (catch ValueError e (:type e)))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout(":ValueError\n")
+        .stderr(predicate::str::contains("Value Error: match: no clause matched \"x\""))
+        .stderr(predicate::str::contains("Syntax Error: match: pattern (i64? n) has no body"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00091() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defprotocol Shape "2D shapes" (area [self] "area of the shape") (perimeter [self]))
+        (struct Rect [w h])
+        (struct Square => Rect [])
+        (extend-type Rect Shape (area [self] (* (:w self) (:h self))) (perimeter [self] (* 2 (+ (:w self) (:h self)))))
+        (extend-type vector Shape (area [self] (len self)))
+        (extend-type nil Shape (area [self] 0))
+        [(area (Rect {:w 2 :h 3})) (perimeter (Rect {:w 2 :h 3})) (area (Square {:w 4 :h 4})) (area [1 2 3]) (area nil)]
+        [(satisfies? Shape [1]) (satisfies? Shape "s") (satisfies? Shape (Square))]
+        area
+        (:perimeter Shape)
+        "##,
+    );
+    let outs = [
+        "Shape",
+        "Rect",
+        "Square",
+        "nil",
+        "nil",
+        "nil",
+        "[6, 10, 16, 3, 0]",
+        "[true, false, true]",
+        "<protocol method: Shape/area>",
+        "<protocol method: Shape/perimeter>",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00092() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defprotocol Shape (area [self]) (perimeter [self]))
+        (extend-type vector Shape (area [self] (len self)))
+        (perimeter [1])
+        (area "s")
+        (extend-type string Shape (volume [self] 1))
+        (extend-type string Unknown (area [self] 1))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("Shape\nnil\n")
+        .stderr(predicate::str::contains(
+            "Type Error: vector does not implement Shape/perimeter",
+        ))
+        .stderr(predicate::str::contains("Type Error: string does not implement Shape/area"))
+        .stderr(predicate::str::contains(
+            "Syntax Error: extend-type: volume is not a method of Shape",
+        ))
+        .stderr(predicate::str::contains("Type Error: Unknown is not a protocol"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00093() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn some "doc of some" [x #i64] [:i64 x])
+        (defn some [x #f64] [:f64 x])
+        [(some 1) (some 1.5)]
+        (defn add [a #number b #number] :number-number)
+        (defn add [a #i64 b #number] :i64-number)
+        [(add 1.0 2.0) (add 1 2.0)]
+        (defn add [a #i64 b #i64] :i64-i64)
+        (add 1 2)
+        (defn len2 [c #collection] :collection)
+        (defn len2 [c #v] :vector)
+        (defn len2 [c #iterable] :iterable)
+        (defn len2 [c] :any)
+        [(len2 [1]) (len2 {:a 1}) (len2 "s") (len2 1)]
+        (struct A [x])
+        (struct B => A [])
+        (defn who [o #A] :A)
+        (defn who [o #B] :B)
+        [(who (A)) (who (B))]
+        [some #i64 (type #i64)]
+        (defn cnt [n #i64 acc] (if (= n 0) acc (cnt (- n 1) (+ acc 1))))
+        (cnt 20000 0)
+        "##,
+    );
+    let outs = [
+        "some",
+        "some",
+        "[[:i64, 1], [:f64, 1.5]]",
+        "add",
+        "add",
+        "[:number-number, :i64-number]",
+        "add",
+        ":i64-i64",
+        "len2",
+        "len2",
+        "len2",
+        "len2",
+        "[:vector, :collection, :iterable, :any]",
+        "A",
+        "B",
+        "who",
+        "who",
+        "[:A, :B]",
+        "[<generic function: some>, #i64, \"type\"]",
+        "cnt",
+        "20000",
+    ];
+    let out = outs.join("\n");
+    cmd.assert().success().stdout(format!("{}\n", out));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00094() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn some [x #i64] x)
+        (some "s")
+        (defn add [a #i64 b #number] 1)
+        (defn add [a #number b #i64] 2)
+        (add 1 2)
+        (defn bad [#i64 x] x)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("some\nadd\nadd\n")
+        .stderr(predicate::str::contains("Type Error: parameter x expected #i64, got string"))
+        .stderr(predicate::str::contains(
+            "Type Error: ambiguous call to add with (i64, i64): candidates are [a #i64 b #number], [a #number b #i64]",
+        ))
+        .stderr(predicate::str::contains(
+            "Syntax Error: defn: type annotation must follow a parameter",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_import() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("scilisp")?;
+    cmd.arg("--path").arg("tests/modules").arg("tests/import.sl");
+    // stats.sl is imported three times but loaded once
+    cmd.assert().success().stdout("loading stats\n2 1 1 1\n");
+    Ok(())
+}
+
+#[test]
+fn execute_import_missing() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("scilisp")?;
+    // a bare file name searches the current directory
+    cmd.current_dir("tests").arg("import_missing.sl");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("module 'nowhere' not found (searched .)"));
+    Ok(())
+}
+
+#[test]
+fn check_types() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("scilisp")?;
+    cmd.arg("check").arg("--types").arg("tests/check.sl");
+    cmd.assert().failure().stderr(
+        "tests/check.sl:2: Type Error: argument 1 of square expected #i64, got #str\n\
+         tests/check.sl:3: Type Error: argument 1 of + expected #number, got #str\n\
+         tests/check.sl:4: Type Error: return value of name expected #str, got #i64\n\
+         tests/check.sl:6: Type Error: binding n expected #i64, got #str\n\
+         tests/check.sl:7: Arity Error: len takes 1 argument, got 2\n\
+         Type Error: 5 error(s) found in tests/check.sl\n",
+    );
+
+    let mut cmd = Command::cargo_bin("scilisp")?;
+    cmd.arg("check").arg("--types").arg("tests/execute.sl");
+    cmd.assert().success().stdout("tests/execute.sl: ok\n");
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00095() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (import "tests/modules/lib/stats.sl" :as st)
+        (st/mean [1 2 3])
+        (import "tests/modules/lib/stats" [variance])
+        (variance [1 3])
+        (st/square-error [1])
+        (st/nothing 1)
+        (import "tests/modules/nope")
+        (import util)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("loading stats\nst\n2\nstats\n1\n")
+        .stderr(predicate::str::contains(
+            "Value Error: square-error is not exported from stats",
+        ))
+        .stderr(predicate::str::contains("Name Error: 'st/nothing' is not defined"))
+        .stderr(predicate::str::contains("Value Error: module 'tests/modules/nope' not found"))
+        .stderr(predicate::str::contains("Value Error: module 'util' not found"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00096() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (struct Enemy [hp] (defn heal [self x] (set! self.hp (+ self.hp x))))
+        (method [Enemy] (defn damage [self x] (set! self.hp (- self.hp x))))
+        (def slime (Enemy {:hp 20}))
+        (.hp slime)
+        (slime.damage 10)
+        slime.hp
+        (.heal slime 5)
+        (:hp slime)
+        (def m {:a {:b 3}, :f inc})
+        m.a.b
+        (m.f 1)
+        (.f m 2)
+        '(.hp slime)
+        'a.b.c
+        slime.mp
+        (.hp)
+        (.hp 1)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("Enemy\nnil\nslime\n20\n:hp\n10\n:hp\n15\nm\n3\n2\n3\n(. slime (:hp))\n(. (. a :b) :c)\n")
+        .stderr(predicate::str::contains("Enemy has no member :mp"))
+        .stderr(predicate::str::contains("Syntax Error: .hp needs a target"))
+        .stderr(predicate::str::contains("Type Error: Cannot get i64 with :hp"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00097() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (enum Color [Red Green Blue])
+        Color.Red
+        (switch Color.Green [Color.Red] "red" [Color.Green] "green")
+        (enum Result [(Ok value) (Err msg)])
+        (def r (Result.Ok 42))
+        r.value
+        (match r (Result.Err e) e (Result.Ok v) (+ v 1))
+        (match Color.Blue Color.Red 1 Color.Blue 3)
+        Color.Purple
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("Color\nColor.Red\n\"green\"\nResult\nr\n42\n43\n3\n")
+        .stderr(predicate::str::contains("Key Error"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00098() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        [#v[#f64] #option[#str] #fn[#i64, #i64] => #i64 #m[#v[#i64]]]
+        (defn avg [xs #v[#f64]] => #f64 (/ (sum xs) (len xs)))
+        (avg [1.0 2.0])
+        (avg [1 2])
+        (avg 1.0)
+        (defn half [x #i64] => #i64 (/ x 2))
+        (half 3)
+        (defn twice [f #fn[#i64] => #i64 x #i64] (f (f x)))
+        (twice inc 1)
+        (twice 1 1)
+        ((fn [x #option[#i64]] x) nil)
+        (defn loop2 [n #i64] => #i64 (if (= n 0) 0 (recur (- n 1))))
+        (loop2 100000)
+        #i64[#f64]
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("[#v[#f64], #option[#str], #fn[#i64 #i64] => #i64, #m[#v[#i64]]]\navg\n1.5\nhalf\ntwice\n3\nnil\nloop2\n0\n")
+        .stderr(predicate::str::contains(
+            "Type Error: parameter xs expected #v[#f64], got vector containing i64",
+        ))
+        .stderr(predicate::str::contains(
+            "Type Error: parameter xs expected #v[#f64], got f64",
+        ))
+        .stderr(predicate::str::contains("Type Error: return value expected #i64, got f64"))
+        .stderr(predicate::str::contains(
+            "Type Error: parameter f expected #fn[#i64] => #i64, got i64",
+        ))
+        .stderr(predicate::str::contains("Syntax Error: invalid type #i64[#f64]"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00099() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (def n #i64 10)
+        (set! n 11)
+        n
+        (set! n "a")
+        (def s #str 1)
+        (const c #option[#i64] nil)
+        (def t #i64)
+        t
+        (def n 1.5)
+        (set! n "b")
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("n\nn\n11\nc\nt\n#i64\nn\nn\n")
+        .stderr(predicate::str::contains("Type Error: binding n expected #i64, got string"))
+        .stderr(predicate::str::contains("Type Error: binding s expected #str, got i64"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00100() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (typedef #int #i64)
+        (typedef #map-key (union #i64 #str #key))
+        #map-key
+        (isa? 1 #int)
+        (isa? 1.5 #map-key)
+        (defn key-name [k #map-key] => #str (str k))
+        (key-name :a)
+        (key-name 1.5)
+        (def n #int 1)
+        (set! n "a")
+        (type-of 1)
+        (= (type-of 1) #int)
+        (typedef #i64 #str)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("#int\n#map-key\n#union[#i64 #str #key]\ntrue\nfalse\nkey-name\n\":a\"\nn\n#i64\ntrue\n")
+        .stderr(predicate::str::contains(
+            "Type Error: parameter k expected #union[#i64 #str #key], got f64",
+        ))
+        .stderr(predicate::str::contains("Type Error: binding n expected #i64, got string"))
+        .stderr(predicate::str::contains("Syntax Error: typedef: cannot redefine #i64"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00101() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (subtype? #i64 #number)
+        (subtype? #number #i64)
+        (subtype? #v[#i64] #collection)
+        (subtype? #str #iterable)
+        (subtype? #option[#i64] (union #nil #number))
+        (struct Animal [name])
+        (struct Dog => Animal [breed])
+        (subtype? #Dog #Animal)
+        (isa? (Dog {:name "a" :breed "b"}) #Animal)
+        (type-of (Dog {:name "a" :breed "b"}))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("true\nfalse\ntrue\ntrue\ntrue\nAnimal\nDog\ntrue\ntrue\n#Dog\n");
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00102() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (->> (range) (map (fn [x] (* x x))) (filter even?) (take 3) (collect))
+        (collect (take-while (fn [x] (< x 3)) (iterate inc 0)))
+        (collect (take 5 (cycle [1 2])))
+        (collect (zip [:a :b :c] (drop 1 (range))))
+        (collect (enumerate "ab"))
+        (collect (chain [1 2] (range 3 5) '(5)))
+        (collect (range 0 10 3))
+        (reduce + 0 (take 4 (range)))
+        (def g (map inc (range 3)))
+        (first g)
+        (collect g)
+        (collect g)
+        (collect (map (fn [x] (+ x "a")) (range 3)))
+        (len (take 2 (range)))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout(
+            "[0, 4, 16]\n[0, 1, 2]\n[1, 2, 1, 2, 1]\n[[:a, 1], [:b, 2], [:c, 3]]\n[[0, \"a\"], [1, \"b\"]]\n\
+             [1, 2, 3, 4, 5]\n[0, 3, 6, 9]\n6\ng\n1\n[2, 3]\n[]\n",
+        )
+        .stderr(predicate::str::contains("Type Error"))
+        .stderr(predicate::str::contains(
+            "len: <generator: take > has no length, collect it first",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00103() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defgen walk [tree] (if (vector? tree) (for [t tree] (for [x (walk t)] (yield x))) (yield tree)))
+        (collect (walk [1 [2 [3 4]] 5]))
+        (defgen naturals [] (loop [i 0] (yield i) (recur (inc i))))
+        (collect (take 3 (map (fn [x] (* x 10)) (naturals))))
+        (3 (naturals))
+        (for [x (walk [1 [2]])] (print x))
+        (def g (naturals))
+        (first g)
+        (collect (take 2 g))
+        (defgen bad [] (yield 1) (+ 1 "a"))
+        (collect (bad))
+        (yield 1)
+        (defgen typed [n #i64] (yield n))
+        (typed "a")
+        (typed)
+        (defgen self [] (yield (first s)))
+        (def s (self))
+        (first s)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("walk\n[1, 2, 3, 4, 5]\nnaturals\n[0, 10, 20]\n3\n1\n2\nnil\ng\n0\n[1, 2]\nbad\ntyped\nself\ns\n")
+        .stderr(predicate::str::contains("Type Error: expected type"))
+        .stderr(predicate::str::contains("yield outside of a generator"))
+        .stderr(predicate::str::contains("parameter n expected #i64, got string"))
+        .stderr(predicate::str::contains("Arity Error"))
+        .stderr(predicate::str::contains("generator is already running"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00104() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (def counter (atom 0))
+        (swap! counter inc)
+        (swap! counter + 10)
+        @counter
+        (reset! counter 5)
+        (compare-and-set! counter 5 6)
+        (compare-and-set! counter 5 7)
+        (defn make-counter [] (let [c (atom 0)] [(fn [] (swap! c inc)) (fn [] @c)]))
+        (def fs (make-counter))
+        ((0 fs))
+        ((0 fs))
+        ((1 fs))
+        (add-watch counter :log (fn [k a old new] (print k old "->" new)))
+        (swap! counter * 2)
+        (remove-watch counter :log)
+        (reset! counter 0)
+        counter
+        (isa? counter #atom)
+        '@counter
+        (deref 1)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout(
+            "counter\n1\n11\n11\n5\ntrue\nfalse\nmake-counter\nfs\n1\n2\n2\n<atom: 6>\n:log 6 -> 12\n12\ntrue\n0\n\
+             <atom: 0>\ntrue\n(deref counter)\n",
+        )
+        .stderr(predicate::str::contains("expected type: 'atom', got: 'i64'"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00105() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (def-dynamic *precision* 6)
+        (defn show [] *precision*)
+        (binding [*precision* 3] (show))
+        (show)
+        (binding [*precision* 3] (+ 1 "a"))
+        (show)
+        (defn early [] (binding [*precision* 1] (return (show))) 99)
+        (early)
+        (show)
+        (let [*precision* 0] (binding [*precision* 4] [*precision* (show)]))
+        (def lexical 1)
+        (binding [lexical 2] lexical)
+        (def-dynamic *p* #i64 1)
+        (binding [*p* "a"] 1)
+        (binding [*p* 2 *precision* 9] [(show) *p*])
+        [*p* *precision*]
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("*precision*\nshow\n3\n6\n6\nearly\n1\n6\n[0, 4]\nlexical\n*p*\n[9, 2]\n[1, 6]\n")
+        .stderr(predicate::str::contains("expected type: 'i64 or f64', got: 'string'"))
+        .stderr(predicate::str::contains(
+            "binding: 'lexical' is not dynamic, define it with def-dynamic",
+        ))
+        .stderr(predicate::str::contains("binding *p* expected #i64, got string"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00106() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        ((partial * 10) 1)
+        ((comp str +) 7 8 9)
+        ((comp) 5)
+        ((juxt min max) [3 1 2])
+        (filter (complement even?) [1 2 3 4])
+        ((constantly 7) 1 2 3)
+        (identity 4)
+        ((fnil + 1 2) nil nil 3)
+        (def calls (atom 0))
+        (defn slow [x] (swap! calls inc) (* x x))
+        (def lru (memoize slow 2))
+        [(lru 1) (lru 2) (lru 1) (lru 3) (lru 1) (lru 2)]
+        @calls
+        (def fib (memoize (fn [n] (if (< n 2) n (+ (fib (- n 1)) (fib (- n 2)))))))
+        (fib 80)
+        (memoize slow 0)
+        (comp inc 1)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("10\n\"24\"\n5\n[1, 3]\n[1, 3]\n7\n4\n6\ncalls\nslow\nlru\n[1, 4, 1, 9, 1, 4]\n4\nfib\n23416728348467685\n")
+        .stderr(predicate::str::contains("memoize: capacity must be positive"))
+        .stderr(predicate::str::contains("expected type: 'function', got: 'i64'"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00107() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn cd [n] => #i64 (if (= n 0) 0 (cd (- n 1))))
+        (cd 200000)
+        (defn bad [n] => #i64 (if (= n 0) "s" (bad (- n 1))))
+        (bad 200000)
+        (defn wrap [n] => #str (cd n))
+        (wrap 3)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("cd\n0\nbad\nwrap\n")
+        .stderr(predicate::str::contains("return value expected #i64, got string"))
+        .stderr(predicate::str::contains("return value expected #str, got i64"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00108() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (import "tests/modules/rows" :as rows)
+        (handler-bind [ValueError (fn [e] (invoke-restart :use-value 0))] (rows/parse-row "bad"))
+        (rows/parse-row "5")
+        (restart-case (rows/parse-row "bad") (:use-value [v] :outer))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("rows\n0\n5\n")
+        .stderr(predicate::str::contains("ValueError: bad row"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00109() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (import "tests/modules/settings" :as s)
+        (s/precision)
+        (binding [s/*precision* 3] (s/precision))
+        (s/precision)
+        (binding [s/*precision* 2] (+ 1 "a"))
+        (s/precision)
+        (binding [s/*nope* 3] 1)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("s\n6\n3\n6\n6\n")
+        .stderr(predicate::str::contains("expected type: 'i64 or f64', got: 'string'"))
+        .stderr(predicate::str::contains("Name Error: 's/*nope*' is not defined"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00110() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn f [n] (if (= n 0) 0 (+ 100 (recur (- n 1)))))
+        (f 3)
+        (loop [i 0] (if (< i 3) [(recur (+ i 1))] i))
+        (defn g [n] (switch n [0] :done :default (recur (- n 1))))
+        (g 100000)
+        (loop [i 0] (when (< i 3) (recur (inc i))))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("f\ng\n:done\nnil\n")
+        .stderr(predicate::str::contains("Syntax Error: recur must be in tail position").count(2));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00111() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn f [x & {:opts opts}] [x opts])
+        (f 1 :opts {:opts 2})
+        (f 1 :opts {:a 1})
+        (f 1 {:opts 3})
+        (f 1 2 {:opts 3})
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("f\n[1, {:opts 2}]\n[1, {:a 1}]\n[1, 3]\n")
+        .stderr(predicate::str::contains(
+            "keyword arguments must be :key value pairs or a map",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00112() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (match [1 2] [x x] :same _ :diff)
+        (match [2 2] [x x] x _ :diff)
+        (match {:a 1 :b [1 3]} {:a x :b [x y]} y _ :no)
+        (def x 5)
+        (match 7 x x)
+        "##,
+    );
+    cmd.assert().success().stdout(":diff\n2\n3\nx\n7\n");
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00113() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.arg("--max-depth").arg("60000");
+    cmd.write_stdin(
+        r##"
+        (defn d [n] (if (= n 0) 0 (+ 1 (d (- n 1)))))
+        (d 10000)
+        (d 100000)
+        "##,
+    );
+    cmd.assert().success().stdout("d\n10000\n").stderr(predicate::str::contains(
+        "Recursion Error: maximum eval depth exceeded (60001)",
+    ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00114() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (enum A [X Y])
+        (enum B [Z Y])
+        Y
+        (enum A [X Y])
+        (def Q 1)
+        (enum C [Q])
+        Q
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("A\nA.Y\nA\nQ\n1\n")
+        .stderr(predicate::str::contains("enum: Y is already defined, cannot define B.Y"))
+        .stderr(predicate::str::contains("enum: Q is already defined, cannot define C.Q"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00115() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (handler-bind [TypeError (fn [c] (print "handled" (:message c)))] (+ 1 "a"))
+        (defn safe-add [a b] (restart-case (+ a b) (:use-value [v] v)))
+        (handler-bind [TypeError (fn [c] (invoke-restart :use-value 0))] [(safe-add 1 2) (safe-add 1 "a")])
+        (handler-bind [NameError (fn [c] (invoke-restart :use-value :undefined))] (restart-case (inc nope) (:use-value [v] v)))
+        (def n (atom 0))
+        (handler-bind [Exception (fn [c] (swap! n inc))] (try (map (fn [x] (+ x "a")) [1 2]) (catch TypeError e :caught)))
+        @n
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("handled expected type: 'i64 or f64', got: 'string'\nsafe-add\n[3, 0]\n:undefined\nn\n:caught\n1\n")
+        .stderr(predicate::str::contains(
+            "TypeError: expected type: 'i64 or f64', got: 'string'",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00116() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn make [] (struct Local [a]) (Local {:a 1}))
+        (:a (make))
+        (ancestor Local)
+        (struct Point [x y])
+        (struct Point [x])
+        (let [_ (struct Point3 => Point [z])] (subtype? #Point3 #Point))
+        (subtype? #Point3 #Point)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("make\n1\nPoint\ntrue\n")
+        .stderr(predicate::str::contains("Name Error: 'Local' is not defined"))
+        .stderr(predicate::str::contains("Name Error: '#Point3' is not defined"))
+        .stderr(predicate::str::contains("Syntax Error: struct: Point is already defined"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00117() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defprotocol P (m [s]))
+        (extend-type tpyo P (m [s] 1))
+        (enum Color [Red])
+        (extend-type Color P (m [s] :color))
+        (struct S [a])
+        (extend-type S P (m [s] :s))
+        (extend-type atom P (m [s] :atom))
+        [(m Red) (m (S)) (m (atom 1))]
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("P\nColor\nnil\nS\nnil\nnil\n[:color, :s, :atom]\n")
+        .stderr(predicate::str::contains("Name Error: 'tpyo' is not defined"));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00118() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (def y #undefined-type 1)
+        (defn f [x #option[#Nope]] x)
+        (typedef #int #i64)
+        (typedef #int #str)
+        (def n #int 1)
+        (struct Node [next] (defn link [self other #Node] (set! self.next other)))
+        (enum Color [Red])
+        (def c #Color Red)
+        (typedef #Color #i64)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("#int\nn\nNode\nColor\nc\n")
+        .stderr(predicate::str::contains("Name Error: '#undefined-type' is not defined"))
+        .stderr(predicate::str::contains("Name Error: '#Nope' is not defined"))
+        .stderr(predicate::str::contains("Syntax Error: typedef: cannot redefine #int"))
+        .stderr(predicate::str::contains(
+            "Syntax Error: typedef: enum Color is already defined",
+        ));
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00119() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (struct C [x] (defn getx [self] self.x))
+        (def c (C {:x 3}))
+        (.getx c)
+        (.x c)
+        (c.getx)
+        (set! (.x c) 5)
+        (.getx c)
+        "##,
+    );
+    cmd.assert().success().stdout("C\nc\n3\n3\n3\n:x\n5\n");
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00120() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (enum Color [Red Green Blue])
+        (defn name [c] (match c Red "red" Color.Green "green" other "other"))
+        [(name Red) (name Green) (name Blue)]
+        (enum Result [(Ok value) (Err msg)])
+        [(Result.Ok? (Ok 1)) (Result.Err? (Ok 1)) (Ok? (Ok 1))]
+        (match (Err "bad") (Result.Ok? r) (:value r) (Result.Err? r) (:msg r))
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("Color\nname\n[\"red\", \"green\", \"other\"]\nResult\n[true, false, true]\n\"bad\"\n");
+    Ok(())
+}
+
+#[test]
+fn execute_repl_00121() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = AssertCmd::cargo_bin("scilisp")?;
+    cmd.write_stdin(
+        r##"
+        (defn k [x #i64] #i64 (* x 2))
+        (fn [] #i64)
+        (defn k [x #i64] => #i64 (* x 2))
+        (k 2)
+        "##,
+    );
+    cmd.assert()
+        .success()
+        .stdout("<function>\nk\n4\n")
+        .stderr(predicate::str::contains(
+            "Syntax Error: defn: the return type must follow =>: [...] => #i64",
+        ));
+    Ok(())
+}